@@ -0,0 +1,58 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, AttributeArgs, Ident, ItemFn, Lit, Meta, NestedMeta};
+
+/// Turns a day's solution into its `main`, which runs it on `inputs/NN.txt`.
+///
+/// `#[aoc2021::main(NN, tools)]` also names a
+/// `fn tools(input: &str, args: &[String]) -> Result<(), Box<dyn Error>>`
+/// that is run instead whenever the binary is given command-line arguments.
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
+    let (day, tools) = match &parse_macro_input!(args as AttributeArgs)[..] {
+        [NestedMeta::Lit(Lit::Int(day))] => (day.clone(), None),
+        [NestedMeta::Lit(Lit::Int(day)), NestedMeta::Meta(Meta::Path(tools))] => {
+            (day.clone(), Some(tools.clone()))
+        }
+        _ => panic!("Expected an integer argument and an optional tools function"),
+    };
+    let input_path = format!("../../inputs/{}.txt", day.token());
+    let run_tools = tools.map(|tools| {
+        quote! {
+          let args = ::std::env::args().skip(1).collect::<Vec<String>>();
+          if !args.is_empty() {
+            if let Err(e) = #tools(INPUT.trim_end(), &args) {
+              eprintln!("Error: {}", e);
+              ::std::process::exit(1);
+            }
+            return;
+          }
+        }
+    });
+
+    let mut aoc_solution = parse_macro_input!(input as ItemFn);
+    aoc_solution.sig.ident = Ident::new("aoc_solution", aoc_solution.sig.ident.span());
+
+    let tokens = quote! {
+      const INPUT: &str = include_str!(#input_path);
+      #aoc_solution
+      fn main() {
+        #run_tools
+        let now = ::std::time::Instant::now();
+        let answers = ::aoc2021::Answers::into_answers(aoc_solution(INPUT.trim_end()));
+        let elapsed = now.elapsed();
+        let (p1, p2) = answers.unwrap_or_else(|e| {
+          eprintln!("Error: {}", e);
+          ::std::process::exit(1);
+        });
+        println!("Part one: {}", p1);
+        println!("Part two: {}", p2);
+        if elapsed.as_millis() > 0 {
+          println!("Time: {}ms", elapsed.as_millis());
+        } else {
+          println!("Time: {}μs", elapsed.as_micros());
+        }
+      }
+    };
+    TokenStream::from(tokens)
+}
@@ -1,26 +1,223 @@
-#![feature(array_windows)]
+use aoc2021::parse::{parse_lines, parse_token, ParseError};
+use itertools::Itertools;
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    str::FromStr,
+};
 
-fn part1(input: &Vec<i32>) -> i32 {
-    input.array_windows().filter(|[a, b]| a < b).count() as i32
+/// How the depths in a window are combined before windows are compared.
+/// Windows are only ever compared with windows of the same size, so `Mean`
+/// ranks them by their sums rather than dividing, which would lose the
+/// fractions that tell close windows apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Aggregate {
+    Sum,
+    Mean,
+    Max,
 }
 
-// a + b + c < b + c + d
-// a < d
-fn part2(input: &Vec<i32>) -> i32 {
-    input.array_windows().filter(|[a, _, _, d]| a < d).count() as i32
+impl FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Aggregate, String> {
+        match s {
+            "sum" => Ok(Aggregate::Sum),
+            "mean" => Ok(Aggregate::Mean),
+            "max" => Ok(Aggregate::Max),
+            _ => Err(format!("expected sum, mean or max, found {:?}", s)),
+        }
+    }
+}
+
+impl Aggregate {
+    /// A value that orders windows of the same size as this aggregate would.
+    fn apply(self, window: &[i32]) -> i64 {
+        match self {
+            Aggregate::Sum | Aggregate::Mean => window.iter().map(|&d| d as i64).sum(),
+            Aggregate::Max => window.iter().max().map_or(0, |&d| d as i64),
+        }
+    }
+}
+
+/// How many times each window's aggregate rose, fell or stayed the same
+/// compared to the window before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Changes {
+    increases: usize,
+    decreases: usize,
+    plateaus: usize,
+}
+
+impl Changes {
+    fn record(&mut self, previous: i64, current: i64) {
+        match previous.cmp(&current) {
+            std::cmp::Ordering::Less => self.increases += 1,
+            std::cmp::Ordering::Greater => self.decreases += 1,
+            std::cmp::Ordering::Equal => self.plateaus += 1,
+        }
+    }
+}
+
+fn count_changes(depths: &[i32], size: usize, aggregate: Aggregate) -> Changes {
+    let mut changes = Changes::default();
+    for (a, b) in depths
+        .windows(size)
+        .map(|window| aggregate.apply(window))
+        .tuple_windows()
+    {
+        changes.record(a, b);
+    }
+    changes
+}
+
+/// The last `size` depths of a stream, kept in a ring buffer so that the
+/// window can slide over any number of depths in constant memory.
+struct RingWindow {
+    depths: Vec<i32>,
+    size: usize,
+    oldest: usize,
+    sum: i64,
+    /// The depths in the window that no later depth is at least as deep as,
+    /// with their positions in the stream. They decrease from front to back,
+    /// so the front is the window's maximum.
+    maxima: VecDeque<(usize, i32)>,
+    seen: usize,
+    aggregate: Aggregate,
+}
+
+impl RingWindow {
+    fn new(size: usize, aggregate: Aggregate) -> RingWindow {
+        RingWindow {
+            // Grown as depths arrive, as `size` may be far larger than the
+            // stream.
+            depths: Vec::new(),
+            size,
+            oldest: 0,
+            sum: 0,
+            maxima: VecDeque::new(),
+            seen: 0,
+            aggregate,
+        }
+    }
+
+    /// Slides the window on to `depth`, returning the window's aggregate as
+    /// soon as it has seen `size` depths.
+    fn push(&mut self, depth: i32) -> Option<i64> {
+        if self.depths.len() < self.size {
+            self.depths.push(depth);
+        } else {
+            self.sum -= self.depths[self.oldest] as i64;
+            self.depths[self.oldest] = depth;
+            self.oldest = (self.oldest + 1) % self.size;
+        }
+        self.sum += depth as i64;
+
+        if self.aggregate == Aggregate::Max {
+            while self.maxima.back().is_some_and(|&(_, d)| d <= depth) {
+                self.maxima.pop_back();
+            }
+            self.maxima.push_back((self.seen, depth));
+            while self
+                .maxima
+                .front()
+                .is_some_and(|&(i, _)| self.seen - i >= self.size)
+            {
+                self.maxima.pop_front();
+            }
+        }
+        self.seen += 1;
+
+        if self.depths.len() < self.size {
+            return None;
+        }
+        Some(match self.aggregate {
+            Aggregate::Sum | Aggregate::Mean => self.sum,
+            Aggregate::Max => self.maxima.front().map_or(0, |&(_, d)| d as i64),
+        })
+    }
+}
+
+/// Counts changes like [`count_changes`], but reads depths from `reader` one
+/// line at a time rather than needing them all in memory.
+fn stream_changes(
+    mut reader: impl BufRead,
+    size: usize,
+    aggregate: Aggregate,
+) -> Result<Changes, Box<dyn Error>> {
+    let mut window = RingWindow::new(size, aggregate);
+    let mut changes = Changes::default();
+    let mut previous = None;
+    let mut line = String::new();
+    for number in 0.. {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let depth = parse_token(&line, &line).map_err(|e| e.offset_lines(number))?;
+        if let Some(current) = window.push(depth) {
+            if let Some(previous) = previous.replace(current) {
+                changes.record(previous, current);
+            }
+        }
+    }
+    Ok(changes)
+}
+
+fn part1(input: &[i32]) -> usize {
+    count_changes(input, 1, Aggregate::Sum).increases
+}
+
+fn part2(input: &[i32]) -> usize {
+    count_changes(input, 3, Aggregate::Sum).increases
+}
+
+fn parse_input(input: &str) -> Result<Vec<i32>, ParseError> {
+    parse_lines(input, |line| parse_token(line, line))
 }
 
-fn parse_input(input: &str) -> Vec<i32> {
-    input
-        .lines()
-        .map(|line| line.trim().parse::<i32>().unwrap())
-        .collect()
+const USAGE: &str = "usage: 01 window <size> [sum|mean|max]
+       01 stream <path|-> <size> [sum|mean|max]";
+
+/// `01 window <size> [sum|mean|max]` counts how often windows of `size`
+/// depths rise, fall or stay level, comparing their sums by default.
+/// `01 stream <path> ...` does the same for the depths in `path`, or on
+/// standard input for `-`, without reading them all into memory.
+fn tools(input: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let (path, size, aggregate) = match args[..] {
+        ["window", size] => (None, size, "sum"),
+        ["window", size, aggregate] => (None, size, aggregate),
+        ["stream", path, size] => (Some(path), size, "sum"),
+        ["stream", path, size, aggregate] => (Some(path), size, aggregate),
+        _ => return Err(USAGE.into()),
+    };
+    let size = match size.parse::<usize>() {
+        Ok(size) if size > 0 => size,
+        _ => return Err(format!("expected a window size of at least 1, found {:?}", size).into()),
+    };
+    let aggregate = aggregate.parse()?;
+
+    let changes = match path {
+        None => count_changes(&parse_input(input)?, size, aggregate),
+        Some("-") => stream_changes(io::stdin().lock(), size, aggregate)?,
+        Some(path) => {
+            let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+            stream_changes(BufReader::new(file), size, aggregate)?
+        }
+    };
+    println!("Increases: {}", changes.increases);
+    println!("Decreases: {}", changes.decreases);
+    println!("Plateaus: {}", changes.plateaus);
+    Ok(())
 }
 
-#[aoc2021::main(01)]
-fn main(input: &str) -> (i32, i32) {
-    let parsed_input = parse_input(input);
-    (part1(&parsed_input), part2(&parsed_input))
+#[aoc2021::main(01, tools)]
+fn main(input: &str) -> Result<(usize, usize), ParseError> {
+    let parsed_input = parse_input(input)?;
+    Ok((part1(&parsed_input), part2(&parsed_input)))
 }
 
 #[cfg(test)]
@@ -40,11 +237,86 @@ mod tests {
 
     #[test]
     fn part_1() {
-        assert_eq!(part1(&parse_input(INPUT)), 7);
+        assert_eq!(part1(&parse_input(INPUT).unwrap()), 7);
     }
 
     #[test]
     fn part_2() {
-        assert_eq!(part2(&parse_input(INPUT)), 5);
+        assert_eq!(part2(&parse_input(INPUT).unwrap()), 5);
+    }
+
+    #[test]
+    fn counts_every_kind_of_change() {
+        let depths = parse_input(INPUT).unwrap();
+        assert_eq!(
+            count_changes(&depths, 1, Aggregate::Sum),
+            Changes {
+                increases: 7,
+                decreases: 2,
+                plateaus: 0
+            }
+        );
+        assert_eq!(
+            count_changes(&depths, 2, Aggregate::Max),
+            Changes {
+                increases: 4,
+                decreases: 2,
+                plateaus: 2
+            }
+        );
+        assert_eq!(
+            count_changes(&depths, 3, Aggregate::Mean),
+            count_changes(&depths, 3, Aggregate::Sum)
+        );
+        assert_eq!(
+            count_changes(&depths, 11, Aggregate::Sum),
+            Changes::default()
+        );
+    }
+
+    #[test]
+    fn streams_the_same_changes() {
+        // A long, bumpy sequence of depths with plenty of plateaus.
+        let depths = (0..5000)
+            .map(|i: i32| (i * 7919 % 211 - 100) + i / 10)
+            .collect::<Vec<_>>();
+        let input = depths.iter().join("\n") + "\n";
+        for size in [1, 2, 3, 17, 5000, 5001, usize::MAX] {
+            for aggregate in [Aggregate::Sum, Aggregate::Mean, Aggregate::Max] {
+                assert_eq!(
+                    stream_changes(input.as_bytes(), size, aggregate).unwrap(),
+                    count_changes(&depths, size, aggregate),
+                    "window of {} by {:?}",
+                    size,
+                    aggregate
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reports_streamed_errors_by_line() {
+        let error = stream_changes("199\n200\n2O8\n".as_bytes(), 1, Aggregate::Sum).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 1: expected i32, found \"2O8\""
+        );
+    }
+
+    #[test]
+    fn parses_aggregates() {
+        assert_eq!("max".parse(), Ok(Aggregate::Max));
+        assert_eq!(
+            "median".parse::<Aggregate>(),
+            Err("expected sum, mean or max, found \"median\"".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_non_numeric_depths() {
+        assert_eq!(
+            parse_input("199\n  2OO\n208"),
+            Err(ParseError::new(2, 3, "expected i32, found \"2OO\""))
+        );
     }
 }
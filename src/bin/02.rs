@@ -1,80 +1,365 @@
-use std::str::FromStr;
+use aoc2021::{
+    parse::{parse_token, ParseError},
+    point::Point,
+    raster::Raster,
+};
+use std::{error::Error, fs, mem, str::FromStr};
 
 enum Direction {
     Forward,
+    Back,
     Down,
     Up,
 }
 
 impl FromStr for Direction {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "forward" => Ok(Direction::Forward),
+            "back" => Ok(Direction::Back),
             "down" => Ok(Direction::Down),
             "up" => Ok(Direction::Up),
-            _ => Err(()),
+            _ => Err(ParseError::new(
+                1,
+                1,
+                format!("expected forward, back, down or up, found {:?}", s),
+            )),
         }
     }
 }
 
 struct Move {
     direction: Direction,
-    distance: i32,
+    distance: i64,
 }
 
-fn parse_input(input: &str) -> Vec<Move> {
-    input
-        .lines()
-        .map(|line| -> Move {
-            let mut split = line.trim().split(" ");
-            Move {
-                direction: Direction::from_str(split.next().unwrap()).unwrap(),
-                distance: split.next().unwrap().parse::<i32>().unwrap(),
+/// One statement of a course. Besides the puzzle's moves, a course may set
+/// the aim outright and repeat a block of statements:
+///
+/// ```text
+/// # comments run to the end of the line
+/// set aim 2
+/// repeat 3 {
+///     forward 5
+///     back 1
+/// }
+/// ```
+enum Command {
+    Move(Move),
+    SetAim(i64),
+    Repeat(usize, Vec<Command>),
+}
+
+/// What a line of a course says, before repeat blocks are put together.
+enum Line {
+    Command(Command),
+    Open(usize),
+    Close,
+}
+
+fn parse_line(line: &str, code: &str) -> Result<Line, ParseError> {
+    let words = code.split_whitespace().collect::<Vec<_>>();
+    Ok(match words[..] {
+        ["}"] => Line::Close,
+        ["repeat", times, "{"] => Line::Open(parse_token(line, times)?),
+        ["set", "aim", aim] => Line::Command(Command::SetAim(parse_token(line, aim)?)),
+        [direction, distance] => Line::Command(Command::Move(Move {
+            direction: Direction::from_str(direction).map_err(|e| e.within(line, direction))?,
+            distance: parse_token(line, distance)?,
+        })),
+        _ => {
+            return Err(ParseError::at(
+                line,
+                code,
+                "expected `<direction> <distance>`",
+            ))
+        }
+    })
+}
+
+/// The most steps a course may take once its repeat blocks are expanded,
+/// counting each command, and each pass through an empty block, as a step.
+const MAX_STEPS: usize = 1_000_000;
+
+fn parse_input(input: &str) -> Result<Vec<Command>, ParseError> {
+    // Each repeat block still open: its line number and text, how many times
+    // it repeats, and the commands and steps of the block around it so far.
+    let mut open = vec![];
+    let mut commands = vec![];
+    let mut steps = 0usize;
+    for (i, line) in input.lines().enumerate() {
+        let code = line.split('#').next().unwrap_or_default().trim();
+        if code.is_empty() {
+            continue;
+        }
+        match parse_line(line, code).map_err(|e| e.offset_lines(i))? {
+            Line::Command(command) => {
+                commands.push(command);
+                steps += 1;
             }
-        })
-        .collect()
+            Line::Open(times) => {
+                open.push((i, line, times, mem::take(&mut commands), steps));
+                steps = 0;
+            }
+            Line::Close => {
+                let (start, start_line, times, outer, outer_steps) = open
+                    .pop()
+                    .ok_or_else(|| ParseError::at(line, code, "unexpected `}`").offset_lines(i))?;
+                let block = mem::replace(&mut commands, outer);
+                commands.push(Command::Repeat(times, block));
+                let total = steps
+                    .max(1)
+                    .checked_mul(times)
+                    .map(|n| n.saturating_add(outer_steps));
+                steps = match total {
+                    Some(total) if total <= MAX_STEPS => total,
+                    _ => {
+                        return Err(ParseError::at(
+                            start_line,
+                            start_line.trim(),
+                            format!("course takes more than {} steps", MAX_STEPS),
+                        )
+                        .offset_lines(start))
+                    }
+                };
+            }
+        }
+    }
+    if let Some((i, line, ..)) = open.pop() {
+        return Err(ParseError::at(line, line.trim(), "unclosed `repeat` block").offset_lines(i));
+    }
+    Ok(commands)
+}
+
+/// How moves change the submarine: part 1 treats `down` and `up` as changes
+/// of depth, while part 2 treats them as changes of aim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Model {
+    Plain,
+    Aim,
 }
 
-fn part1(moves: &Vec<Move>) -> i32 {
-    let mut horiz = 0;
-    let mut depth = 0;
+impl FromStr for Model {
+    type Err = String;
 
-    for m in moves {
-        match m.direction {
-            Direction::Forward => horiz += m.distance,
-            Direction::Down => depth += m.distance,
-            Direction::Up => depth -= m.distance,
+    fn from_str(s: &str) -> Result<Model, String> {
+        match s {
+            "plain" => Ok(Model::Plain),
+            "aim" => Ok(Model::Aim),
+            _ => Err(format!("expected plain or aim, found {:?}", s)),
         }
     }
+}
 
-    depth * horiz
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Submarine {
+    horiz: i64,
+    depth: i64,
+    aim: i64,
 }
 
-fn part2(moves: &Vec<Move>) -> i32 {
-    let mut aim = 0;
-    let mut horiz = 0;
-    let mut depth = 0;
+const OVERFLOW: &str = "the submarine went further than an i64 can hold";
 
-    for m in moves {
-        match m.direction {
-            Direction::Forward => {
-                horiz += m.distance;
-                depth += aim * m.distance;
+impl Submarine {
+    /// Makes the move `m`, failing rather than wrapping if any of the
+    /// submarine's numbers overflow.
+    fn apply(&mut self, model: Model, m: &Move) -> Result<(), String> {
+        let (forward, down) = match m.direction {
+            Direction::Forward => (m.distance, 0),
+            Direction::Back => (m.distance.checked_neg().ok_or(OVERFLOW)?, 0),
+            Direction::Down => (0, m.distance),
+            Direction::Up => (0, m.distance.checked_neg().ok_or(OVERFLOW)?),
+        };
+        let mut next = *self;
+        next.horiz = self.horiz.checked_add(forward).ok_or(OVERFLOW)?;
+        match model {
+            Model::Plain => next.depth = self.depth.checked_add(down).ok_or(OVERFLOW)?,
+            Model::Aim => {
+                next.aim = self.aim.checked_add(down).ok_or(OVERFLOW)?;
+                next.depth = next
+                    .aim
+                    .checked_mul(forward)
+                    .and_then(|change| self.depth.checked_add(change))
+                    .ok_or(OVERFLOW)?;
             }
-            Direction::Down => aim += m.distance,
-            Direction::Up => aim -= m.distance,
         }
+        *self = next;
+        Ok(())
     }
 
-    depth * horiz
+    fn run(&mut self, model: Model, commands: &[Command]) -> Result<(), String> {
+        self.run_recording(model, commands, &mut |_| {})
+    }
+
+    /// Runs `commands`, calling `record` with the submarine after each move.
+    fn run_recording(
+        &mut self,
+        model: Model,
+        commands: &[Command],
+        record: &mut impl FnMut(&Submarine),
+    ) -> Result<(), String> {
+        for command in commands {
+            match command {
+                Command::Move(m) => {
+                    self.apply(model, m)?;
+                    record(self);
+                }
+                Command::SetAim(aim) => self.aim = *aim,
+                Command::Repeat(times, block) => {
+                    for _ in 0..*times {
+                        self.run_recording(model, block, record)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn product(&self) -> Result<i64, String> {
+        self.horiz
+            .checked_mul(self.depth)
+            .ok_or_else(|| OVERFLOW.to_string())
+    }
+}
+
+/// Where the submarine is before the first move and after each one.
+fn trajectory(commands: &[Command], model: Model) -> Result<Vec<Submarine>, String> {
+    let mut submarine = Submarine::default();
+    let mut trajectory = vec![submarine];
+    submarine.run_recording(model, commands, &mut |s| trajectory.push(*s))?;
+    Ok(trajectory)
+}
+
+fn to_csv(trajectory: &[Submarine]) -> String {
+    let mut csv = String::from("step,horiz,depth,aim\n");
+    for (step, s) in trajectory.iter().enumerate() {
+        csv += &format!("{},{},{},{}\n", step, s.horiz, s.depth, s.aim);
+    }
+    csv
+}
+
+/// Draws the path of `trajectory` as seen from the side, with horizontal
+/// position across and depth increasing downwards, scaled to fit `width` by
+/// `height` characters.
+fn depth_profile(trajectory: &[Submarine], width: usize, height: usize) -> String {
+    let (min_h, max_h) = bounds(trajectory.iter().map(|s| s.horiz));
+    let (min_d, max_d) = bounds(trajectory.iter().map(|s| s.depth));
+    let scale = |value: i64, min: i64, max: i64, cells: usize| {
+        // Widened first, as the range may not fit in an i64.
+        let cells = cells as i128 - 1;
+        let range = (max as i128 - min as i128).max(1);
+        ((value as i128 - min as i128) * cells / range) as i64
+    };
+    let cell = |s: &Submarine| {
+        Point::new(
+            scale(s.horiz, min_h, max_h, width),
+            scale(s.depth, min_d, max_d, height),
+        )
+    };
+
+    let mut rows = vec![vec![' '; width]; height];
+    for (a, b) in trajectory.iter().zip(trajectory.iter().skip(1)) {
+        for p in Raster::new(cell(a), cell(b)) {
+            rows[p.y as usize][p.x as usize] = '*';
+        }
+    }
+    if let [only] = trajectory {
+        let p = cell(only);
+        rows[p.y as usize][p.x as usize] = '*';
+    }
+
+    let label_width = min_d.to_string().len().max(max_d.to_string().len());
+    let mut profile = String::new();
+    for (y, row) in rows.iter().enumerate() {
+        let label = match y {
+            0 => min_d.to_string(),
+            _ if y == height - 1 => max_d.to_string(),
+            _ => String::new(),
+        };
+        let row = row.iter().collect::<String>();
+        profile += &format!("{:>w$} |{}\n", label, row.trim_end(), w = label_width);
+    }
+    profile += &format!("{:w$} +{}\n", "", "-".repeat(width), w = label_width);
+    let (left, right) = (min_h.to_string(), max_h.to_string());
+    profile += &format!(
+        "{:w$}  {}{:>r$}\n",
+        "",
+        left,
+        right,
+        w = label_width,
+        r = width.saturating_sub(left.len()),
+    );
+    profile
+}
+
+fn bounds(values: impl Iterator<Item = i64>) -> (i64, i64) {
+    values.fold((i64::MAX, i64::MIN), |(min, max), v| {
+        (min.min(v), max.max(v))
+    })
 }
 
-#[aoc2021::main(02)]
-fn main(input: &str) -> (i32, i32) {
-    let parsed = parse_input(input);
-    (part1(&parsed), part2(&parsed))
+fn final_product(commands: &[Command], model: Model) -> Result<i64, String> {
+    let mut submarine = Submarine::default();
+    submarine.run(model, commands)?;
+    submarine.product()
+}
+
+fn part1(commands: &[Command]) -> Result<i64, String> {
+    final_product(commands, Model::Plain)
+}
+
+fn part2(commands: &[Command]) -> Result<i64, String> {
+    final_product(commands, Model::Aim)
+}
+
+const USAGE: &str = "usage: 02 <run|trace|plot> <plain|aim> [<course>]";
+
+const PLOT_WIDTH: usize = 72;
+const PLOT_HEIGHT: usize = 20;
+
+/// `02 run <model> [<course>]` steers the submarine through the course in the
+/// given file, or the puzzle input, and reports where it ends up. `trace`
+/// prints its position after every move as CSV instead, and `plot` draws its
+/// depth profile.
+fn tools(input: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let (tool, model, path) = match args[..] {
+        [tool, model] => (tool, model, None),
+        [tool, model, path] => (tool, model, Some(path)),
+        _ => return Err(USAGE.into()),
+    };
+    let model = model.parse()?;
+    let course = match path {
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?,
+        None => input.to_string(),
+    };
+    let commands = parse_input(&course)?;
+
+    match tool {
+        "run" => {
+            let mut submarine = Submarine::default();
+            submarine.run(model, &commands)?;
+            println!("Horizontal position: {}", submarine.horiz);
+            println!("Depth: {}", submarine.depth);
+            println!("Aim: {}", submarine.aim);
+            println!("Product: {}", submarine.product()?);
+        }
+        "trace" => print!("{}", to_csv(&trajectory(&commands, model)?)),
+        "plot" => print!(
+            "{}",
+            depth_profile(&trajectory(&commands, model)?, PLOT_WIDTH, PLOT_HEIGHT)
+        ),
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
+
+#[aoc2021::main(02, tools)]
+fn main(input: &str) -> Result<(i64, i64), Box<dyn Error>> {
+    let parsed = parse_input(input)?;
+    Ok((part1(&parsed)?, part2(&parsed)?))
 }
 
 #[cfg(test)]
@@ -90,11 +375,183 @@ forward 2";
 
     #[test]
     fn part_1() {
-        assert_eq!(part1(&parse_input(INPUT)), 150);
+        assert_eq!(part1(&parse_input(INPUT).unwrap()), Ok(150));
     }
 
     #[test]
     fn part_2() {
-        assert_eq!(part2(&parse_input(INPUT)), 900);
+        assert_eq!(part2(&parse_input(INPUT).unwrap()), Ok(900));
+    }
+
+    #[test]
+    fn rejects_unknown_directions() {
+        assert_eq!(
+            parse_input("forward 5\nsideways 3").err(),
+            Some(ParseError::new(
+                2,
+                1,
+                "expected forward, back, down or up, found \"sideways\""
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_moves() {
+        assert_eq!(
+            parse_input("forward 5\ndown").err(),
+            Some(ParseError::new(2, 1, "expected `<direction> <distance>`"))
+        );
+        assert_eq!(
+            parse_input("up x").err(),
+            Some(ParseError::new(1, 4, "expected i64, found \"x\""))
+        );
+    }
+
+    #[test]
+    fn runs_extended_courses() {
+        let course = "# dive, then level out
+down 4
+repeat 2 {
+    forward 3 # twice
+    repeat 2 {
+        up 1
+    }
+}
+back 2
+set aim 5
+forward 1";
+        let commands = parse_input(course).unwrap();
+
+        let mut plain = Submarine::default();
+        plain.run(Model::Plain, &commands).unwrap();
+        assert_eq!(
+            plain,
+            Submarine {
+                horiz: 5,
+                depth: 0,
+                aim: 5
+            }
+        );
+
+        let mut aim = Submarine::default();
+        aim.run(Model::Aim, &commands).unwrap();
+        assert_eq!(
+            aim,
+            Submarine {
+                horiz: 5,
+                depth: 4 * 3 + 2 * 3 + 5,
+                aim: 5
+            }
+        );
+    }
+
+    #[test]
+    fn records_trajectories() {
+        let commands = parse_input("forward 2\ndown 1\nrepeat 2 {\n  forward 1\n}").unwrap();
+        let trajectory = trajectory(&commands, Model::Aim).unwrap();
+        assert_eq!(trajectory.len(), 5);
+        assert_eq!(
+            to_csv(&trajectory),
+            "step,horiz,depth,aim
+0,0,0,0
+1,2,0,0
+2,2,0,1
+3,3,1,1
+4,4,2,1
+"
+        );
+    }
+
+    #[test]
+    fn plots_depth_profiles() {
+        let commands = parse_input("forward 2\ndown 4\nforward 4").unwrap();
+        let profile = depth_profile(&trajectory(&commands, Model::Aim).unwrap(), 7, 4);
+        assert_eq!(
+            profile.lines().collect::<Vec<_>>(),
+            [
+                " 0 |***",
+                "   |   **",
+                "   |     *",
+                "16 |      *",
+                "   +-------",
+                "    0     6",
+            ]
+        );
+    }
+
+    #[test]
+    fn plots_extreme_depths() {
+        let trajectory = [i64::MIN, 0, i64::MAX].map(|depth| Submarine {
+            horiz: depth,
+            depth,
+            aim: 0,
+        });
+        let profile = depth_profile(&trajectory, 3, 3);
+        assert_eq!(
+            profile.lines().take(3).collect::<Vec<_>>(),
+            [
+                "-9223372036854775808 |*",
+                "                     | *",
+                " 9223372036854775807 |  *",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unbalanced_blocks() {
+        assert_eq!(
+            parse_input("forward 1\n}").err(),
+            Some(ParseError::new(2, 1, "unexpected `}`"))
+        );
+        assert_eq!(
+            parse_input("repeat 2 {\n  repeat 3 {\n  }\nforward 1").err(),
+            Some(ParseError::new(1, 1, "unclosed `repeat` block"))
+        );
+        assert_eq!(
+            parse_input("  repeat x {").err(),
+            Some(ParseError::new(1, 10, "expected usize, found \"x\""))
+        );
+    }
+
+    #[test]
+    fn rejects_courses_that_take_too_many_steps() {
+        assert_eq!(
+            parse_input("forward 1\nrepeat 1000 {\n  repeat 1001 {\n  }\n}").err(),
+            Some(ParseError::new(
+                2,
+                1,
+                "course takes more than 1000000 steps"
+            ))
+        );
+        assert!(parse_input("repeat 1000 {\n  repeat 1000 {\n  }\n}").is_ok());
+        assert_eq!(
+            parse_input(&format!("repeat {} {{\n  up 1\n  up 1\n}}", usize::MAX)).err(),
+            Some(ParseError::new(
+                1,
+                1,
+                "course takes more than 1000000 steps"
+            ))
+        );
+    }
+
+    #[test]
+    fn reports_overflow() {
+        let commands = parse_input("forward 9223372036854775807\nforward 1").unwrap();
+        assert_eq!(part1(&commands), Err(OVERFLOW.to_string()));
+        let commands = parse_input("forward 4294967296\ndown 4294967296").unwrap();
+        assert_eq!(part1(&commands), Err(OVERFLOW.to_string()));
+        let commands = parse_input("down 4294967296\nforward 4294967296").unwrap();
+        assert_eq!(part2(&commands), Err(OVERFLOW.to_string()));
+        let commands = parse_input("back -9223372036854775808").unwrap();
+        assert_eq!(part1(&commands), Err(OVERFLOW.to_string()));
+    }
+
+    #[test]
+    fn parses_models() {
+        assert_eq!("aim".parse(), Ok(Model::Aim));
+        assert_eq!(
+            "sonar".parse::<Model>(),
+            Err("expected plain or aim, found \"sonar\"".to_string())
+        );
     }
 }
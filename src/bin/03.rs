@@ -1,110 +1,200 @@
-use std::vec;
-
-#[aoc2021::main(03)]
-fn main(input: &str) -> (u32, u32) {
-    let input_width = input.lines().next().unwrap().len();
-    let parsed_input = parse_input(input);
-    (
-        part1(&parsed_input, input_width),
-        part2(&parsed_input, input_width),
-    )
-}
-
-fn parse_input(input: &str) -> Vec<u32> {
-    input
-        .lines()
-        .map(|line| u32::from_str_radix(line.trim(), 2).unwrap())
-        .collect()
-}
-
-fn most_common_bit(input: &Vec<u32>, idx: usize) -> Option<u32> {
-    let mut zeroes = 0;
-    let mut ones = 0;
-    for i in input {
-        let mut val = *i;
-        val >>= idx;
-        val &= 1;
-        if val == 1 {
-            ones += 1;
-        } else {
-            zeroes += 1;
+use aoc2021::{
+    bits::{BitTrie, Bits},
+    parse::{parse_lines, ParseError},
+};
+use std::{cmp::Ordering, error::Error, str::FromStr};
+
+#[aoc2021::main(03, tools)]
+fn main(input: &str) -> Result<(u64, u64), Box<dyn Error>> {
+    let parsed_input = parse_input(input)?;
+    Ok((part1(&parsed_input)?, part2(&parsed_input)?))
+}
+
+fn parse_input(input: &str) -> Result<Vec<Bits>, ParseError> {
+    let width = input.lines().next().unwrap_or_default().trim().len();
+    parse_lines(input, |line| {
+        let trimmed = line.trim();
+        let bits = trimmed
+            .parse::<Bits>()
+            .map_err(|e| e.within(line, trimmed))?;
+        if bits.len() != width {
+            return Err(ParseError::at(
+                line,
+                trimmed,
+                format!("expected {} bits, found {}", width, bits.len()),
+            ));
         }
-    }
+        Ok(bits)
+    })
+}
 
-    if ones > zeroes {
-        Some(1)
-    } else if zeroes > ones {
-        Some(0)
-    } else {
-        None
+/// The more common of two bits given how many of each there are, or `None`
+/// if they are equally common.
+fn most_common(zeros: usize, ones: usize) -> Option<bool> {
+    match ones.cmp(&zeros) {
+        Ordering::Greater => Some(true),
+        Ordering::Less => Some(false),
+        Ordering::Equal => None,
     }
 }
 
-fn part1(input: &Vec<u32>, input_width: usize) -> u32 {
-    let mut most_common = 0;
-    let mut least_common = 0;
-    for i in (0..input_width).rev() {
-        let bit = most_common_bit(input, i).unwrap_or(1);
-        if bit == 1 {
-            most_common += 1 << i;
-        } else {
-            least_common += 1 << i;
+/// How many of `input` have a zero and a one as their `idx`th bit from the
+/// left.
+fn bit_counts(input: &[Bits], idx: usize) -> (usize, usize) {
+    let ones = input.iter().filter(|bits| bits.get(idx)).count();
+    (input.len() - ones, ones)
+}
+
+/// How to pick a bit given how many numbers have a zero and a one there.
+#[derive(Debug, Clone, Copy)]
+enum Criterion {
+    MostCommon {
+        tie: bool,
+    },
+    LeastCommon {
+        tie: bool,
+    },
+    /// Called with the index of the bit, then the counts of zeros and ones.
+    Custom(fn(usize, usize, usize) -> bool),
+}
+
+impl Criterion {
+    fn choose(self, idx: usize, zeros: usize, ones: usize) -> bool {
+        match self {
+            Criterion::MostCommon { tie } => most_common(zeros, ones).unwrap_or(tie),
+            Criterion::LeastCommon { tie } => most_common(zeros, ones).map_or(tie, |bit| !bit),
+            Criterion::Custom(choose) => choose(idx, zeros, ones),
         }
     }
-    most_common * least_common
 }
 
-fn ox_gen_rating(input: &Vec<u32>, input_width: usize) -> u32 {
-    let mut ox_gen_ratings: Vec<u32> = input.clone();
-    for i in (0..input_width).rev() {
-        let bit = most_common_bit(&ox_gen_ratings, i).unwrap_or(1);
-        let mut working_input = vec![];
-        for j in 0..ox_gen_ratings.len() {
-            let mut val = ox_gen_ratings[j];
-            val = (val >> i) & 1;
-            if val == bit {
-                working_input.push(ox_gen_ratings[j]);
-            }
+impl FromStr for Criterion {
+    type Err = String;
+
+    /// Parses `most:<tie>` or `least:<tie>`, where `<tie>` is the bit picked
+    /// when zeros and ones are equally common, or `0` or `1` to always pick
+    /// that bit.
+    fn from_str(s: &str) -> Result<Criterion, String> {
+        match s {
+            "most:0" => Ok(Criterion::MostCommon { tie: false }),
+            "most:1" => Ok(Criterion::MostCommon { tie: true }),
+            "least:0" => Ok(Criterion::LeastCommon { tie: false }),
+            "least:1" => Ok(Criterion::LeastCommon { tie: true }),
+            "0" => Ok(Criterion::Custom(|_, _, _| false)),
+            "1" => Ok(Criterion::Custom(|_, _, _| true)),
+            _ => Err(format!(
+                "expected most:0, most:1, least:0, least:1, 0 or 1, found {:?}",
+                s
+            )),
         }
+    }
+}
+
+/// The bit criteria for every number the diagnostic report is used for.
+#[derive(Debug, Clone, Copy)]
+struct Rules {
+    gamma: Criterion,
+    epsilon: Criterion,
+    oxygen: Criterion,
+    co2: Criterion,
+}
 
-        ox_gen_ratings = working_input;
-        if ox_gen_ratings.len() == 1 {
-            break;
+impl Default for Rules {
+    /// The puzzle's rules.
+    fn default() -> Rules {
+        Rules {
+            gamma: Criterion::MostCommon { tie: true },
+            epsilon: Criterion::LeastCommon { tie: false },
+            oxygen: Criterion::MostCommon { tie: true },
+            co2: Criterion::LeastCommon { tie: false },
         }
     }
+}
 
-    ox_gen_ratings[0]
+fn to_u64(bits: &Bits) -> Result<u64, String> {
+    bits.to_int()
+        .ok_or_else(|| format!("{} does not fit in 64 bits", bits))
 }
 
-fn co2_scrub_rating(input: &Vec<u32>, input_width: usize) -> u32 {
-    let mut co2_scrub_ratings: Vec<u32> = input.clone();
-    for i in (0..input_width).rev() {
-        let bit = if most_common_bit(&co2_scrub_ratings, i).unwrap_or(1) == 1 {
-            0
-        } else {
-            1
-        };
+fn product(a: u64, b: u64) -> Result<u64, String> {
+    a.checked_mul(b)
+        .ok_or_else(|| format!("{} * {} does not fit in 64 bits", a, b))
+}
 
-        let mut working_input = vec![];
-        for j in 0..co2_scrub_ratings.len() {
-            let mut val = co2_scrub_ratings[j];
-            val = (val >> i) & 1;
-            if val == bit {
-                working_input.push(co2_scrub_ratings[j]);
-            }
-        }
+/// The gamma and epsilon rates, built from the bit `criterion` picks in each
+/// column of `input`.
+fn rate(input: &[Bits], criterion: Criterion) -> Result<u64, String> {
+    let width = input.first().map_or(0, Bits::len);
+    let rate = (0..width)
+        .map(|i| {
+            let (zeros, ones) = bit_counts(input, i);
+            criterion.choose(i, zeros, ones)
+        })
+        .collect::<Bits>();
+    to_u64(&rate)
+}
 
-        co2_scrub_ratings = working_input;
-        if co2_scrub_ratings.len() == 1 {
-            break;
-        }
-    }
+fn power_consumption(input: &[Bits], rules: &Rules) -> Result<u64, String> {
+    product(rate(input, rules.gamma)?, rate(input, rules.epsilon)?)
+}
 
-    co2_scrub_ratings[0]
+/// Keeps narrowing `input` down one bit at a time, from the left, to the
+/// numbers whose bit `criterion` chooses given how many of those left have
+/// each bit there, until only one number remains. A bit that none of them
+/// have is never chosen.
+fn rating(input: &BitTrie, criterion: Criterion) -> Result<u64, String> {
+    let bits = input
+        .search(|i, zeros, ones| criterion.choose(i, zeros, ones))
+        .ok_or("no numbers to rate")?;
+    to_u64(&bits)
 }
 
-fn part2(input: &Vec<u32>, input_width: usize) -> u32 {
-    ox_gen_rating(input, input_width) * co2_scrub_rating(input, input_width)
+fn life_support_rating(input: &[Bits], rules: &Rules) -> Result<u64, String> {
+    let trie = input.iter().collect::<BitTrie>();
+    product(rating(&trie, rules.oxygen)?, rating(&trie, rules.co2)?)
+}
+
+fn part1(input: &[Bits]) -> Result<u64, String> {
+    power_consumption(input, &Rules::default())
+}
+
+fn part2(input: &[Bits]) -> Result<u64, String> {
+    life_support_rating(input, &Rules::default())
+}
+
+const USAGE: &str = "usage: 03 power <gamma> <epsilon>
+       03 life-support <oxygen> <co2>
+where each criterion is most:<tie>, least:<tie>, 0 or 1";
+
+/// `03 power <gamma> <epsilon>` and `03 life-support <oxygen> <co2>` answer
+/// the puzzle's parts with other bit criteria, such as `most:0` to keep the
+/// most common bit but zeros on a tie.
+fn tools(input: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let input = parse_input(input)?;
+    let mut rules = Rules::default();
+    match args[..] {
+        ["power", gamma, epsilon] => {
+            rules.gamma = gamma.parse()?;
+            rules.epsilon = epsilon.parse()?;
+            println!("Gamma rate: {}", rate(&input, rules.gamma)?);
+            println!("Epsilon rate: {}", rate(&input, rules.epsilon)?);
+            println!("Power consumption: {}", power_consumption(&input, &rules)?);
+        }
+        ["life-support", oxygen, co2] => {
+            rules.oxygen = oxygen.parse()?;
+            rules.co2 = co2.parse()?;
+            let trie = input.iter().collect::<BitTrie>();
+            println!("Oxygen generator rating: {}", rating(&trie, rules.oxygen)?);
+            println!("CO2 scrubber rating: {}", rating(&trie, rules.co2)?);
+            println!(
+                "Life support rating: {}",
+                life_support_rating(&input, &rules)?
+            );
+        }
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -123,43 +213,183 @@ mod tests {
 11001
 00010
 01010";
-    const INPUT_WIDTH: usize = 5;
 
     #[test]
     fn test_parse_input() {
-        let parsed = parse_input(INPUT);
-        assert_eq!(parsed[0], 0b00100);
-        assert_eq!(parsed[1], 0b11110);
-        assert_eq!(parsed[2], 0b10110);
-        assert_eq!(parsed[3], 0b10111);
-        assert_eq!(parsed[4], 0b10101);
-        assert_eq!(parsed[5], 0b01111);
-        assert_eq!(parsed[6], 0b00111);
-        assert_eq!(parsed[7], 0b11100);
-        assert_eq!(parsed[8], 0b10000);
-        assert_eq!(parsed[9], 0b11001);
-        assert_eq!(parsed[10], 0b00010);
-        assert_eq!(parsed[11], 0b01010);
+        let parsed = parse_input(INPUT).unwrap();
+        assert_eq!(parsed[0].to_int::<u32>(), Some(0b00100));
+        assert_eq!(parsed[1].to_int::<u32>(), Some(0b11110));
+        assert_eq!(parsed[2].to_int::<u32>(), Some(0b10110));
+        assert_eq!(parsed[3].to_int::<u32>(), Some(0b10111));
+        assert_eq!(parsed[4].to_int::<u32>(), Some(0b10101));
+        assert_eq!(parsed[5].to_int::<u32>(), Some(0b01111));
+        assert_eq!(parsed[6].to_int::<u32>(), Some(0b00111));
+        assert_eq!(parsed[7].to_int::<u32>(), Some(0b11100));
+        assert_eq!(parsed[8].to_int::<u32>(), Some(0b10000));
+        assert_eq!(parsed[9].to_int::<u32>(), Some(0b11001));
+        assert_eq!(parsed[10].to_int::<u32>(), Some(0b00010));
+        assert_eq!(parsed[11].to_int::<u32>(), Some(0b01010));
     }
 
     #[test]
     fn test_most_common_bit_at_i() {
-        let parsed_input = parse_input(INPUT);
-        assert_eq!(most_common_bit(&parsed_input, 4), Some(1));
-        assert_eq!(most_common_bit(&parsed_input, 3), Some(0));
-        assert_eq!(most_common_bit(&parsed_input, 2), Some(1));
-        assert_eq!(most_common_bit(&parsed_input, 1), Some(1));
-        assert_eq!(most_common_bit(&parsed_input, 0), Some(0));
-        assert_eq!(most_common_bit(&vec![0, 1], 0), None);
+        let most_common_bit = |input: &[Bits], i| {
+            let (zeros, ones) = bit_counts(input, i);
+            most_common(zeros, ones)
+        };
+        let parsed_input = parse_input(INPUT).unwrap();
+        assert_eq!(most_common_bit(&parsed_input, 0), Some(true));
+        assert_eq!(most_common_bit(&parsed_input, 1), Some(false));
+        assert_eq!(most_common_bit(&parsed_input, 2), Some(true));
+        assert_eq!(most_common_bit(&parsed_input, 3), Some(true));
+        assert_eq!(most_common_bit(&parsed_input, 4), Some(false));
+        assert_eq!(most_common_bit(&parse_input("0\n1").unwrap(), 0), None);
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(INPUT), INPUT_WIDTH), 198);
+        assert_eq!(part1(&parse_input(INPUT).unwrap()), Ok(198));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(INPUT), INPUT_WIDTH), 230);
+        assert_eq!(part2(&parse_input(INPUT).unwrap()), Ok(230));
+    }
+
+    #[test]
+    fn handles_lines_wider_than_32_bits() {
+        let input = "0000000000000000000000000000000000000011
+0000000000000000000000000000000000000011
+1111111111111111111111111111111111111100";
+        let parsed = parse_input(input).unwrap();
+        assert_eq!(parsed[2].to_int::<u64>(), Some((1 << 40) - 4));
+        assert_eq!(part1(&parsed), Ok(3 * ((1 << 40) - 4)));
+        assert_eq!(part2(&parsed), Ok(3 * ((1 << 40) - 4)));
+    }
+
+    /// The puzzle's rating process, refiltering every number at every bit.
+    /// Like the trie, it never filters out every number that is left.
+    fn naive_rating(input: &[Bits], criterion: Criterion) -> u64 {
+        let mut ratings = input.to_vec();
+        for i in 0..input[0].len() {
+            let ones = ratings.iter().filter(|bits| bits.get(i)).count();
+            let bit = match (ratings.len() - ones, ones) {
+                (_, 0) => false,
+                (0, _) => true,
+                (zeros, ones) => criterion.choose(i, zeros, ones),
+            };
+            ratings.retain(|bits| bits.get(i) == bit);
+            if ratings.len() == 1 {
+                break;
+            }
+        }
+        to_u64(&ratings[0]).unwrap()
+    }
+
+    /// `count` pseudo-random numbers of `width` bits, with some repeats.
+    fn generated_input(count: usize, width: usize, seed: u64) -> Vec<Bits> {
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                // Skew the high bits so the ratings take a few steps to find.
+                let value = (state >> 20) & (state >> 33);
+                (0..width)
+                    .map(|i| value >> (width - 1 - i) & 1 == 1)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn criteria() -> [Criterion; 5] {
+        [
+            Criterion::MostCommon { tie: true },
+            Criterion::MostCommon { tie: false },
+            Criterion::LeastCommon { tie: false },
+            Criterion::LeastCommon { tie: true },
+            Criterion::Custom(|i, zeros, ones| {
+                if i % 2 == 0 {
+                    ones > zeros
+                } else {
+                    ones * 3 < zeros
+                }
+            }),
+        ]
+    }
+
+    #[test]
+    fn trie_ratings_match_filtering() {
+        for (count, width, seed) in [(20_000, 24, 1), (5_000, 12, 2), (50_000, 40, 3), (2, 8, 4)] {
+            let input = generated_input(count, width, seed);
+            let trie = input.iter().collect::<BitTrie>();
+            for criterion in criteria() {
+                assert_eq!(
+                    rating(&trie, criterion),
+                    Ok(naive_rating(&input, criterion)),
+                    "{:?} rating for {} numbers of {} bits",
+                    criterion,
+                    count,
+                    width
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn applies_other_rules() {
+        let ties = parse_input("0110\n1010").unwrap();
+        let mut rules = Rules::default();
+        assert_eq!(rate(&ties, rules.gamma), Ok(0b1110));
+        assert_eq!(rate(&ties, rules.epsilon), Ok(0b0001));
+        rules.gamma = "most:0".parse().unwrap();
+        rules.epsilon = "least:1".parse().unwrap();
+        assert_eq!(power_consumption(&ties, &rules), Ok(0b0010 * 0b1101));
+
+        let input = parse_input(INPUT).unwrap();
+        rules.oxygen = "1".parse().unwrap();
+        rules.co2 = "0".parse().unwrap();
+        assert_eq!(life_support_rating(&input, &rules), Ok(0b11110 * 0b00010));
+        assert!("median:1".parse::<Criterion>().is_err());
+    }
+
+    #[test]
+    fn reports_ratings_too_wide_for_64_bits() {
+        // Every column is tied, so gamma has every bit set.
+        let wide = format!("1{}\n0{}", "0".repeat(64), "1".repeat(64));
+        let parsed = parse_input(&wide).unwrap();
+        assert_eq!(
+            part1(&parsed),
+            Err(format!("{} does not fit in 64 bits", "1".repeat(65)))
+        );
+        // The ratings start with a 0 bit, so they fit even though the
+        // numbers are 65 bits wide, but their product does not.
+        let narrow = format!("0{}\n0{}", "1".repeat(64), "1".repeat(63) + "0");
+        assert_eq!(
+            part2(&parse_input(&narrow).unwrap()),
+            Err(format!(
+                "{} * {} does not fit in 64 bits",
+                u64::MAX,
+                u64::MAX - 1
+            ))
+        );
+        assert_eq!(part2(&[]), Err("no numbers to rate".to_string()));
+    }
+
+    #[test]
+    fn rejects_non_binary_digits() {
+        assert_eq!(
+            parse_input("00100\n11210"),
+            Err(ParseError::new(2, 3, "expected 0 or 1, found '2'"))
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_widths() {
+        assert_eq!(
+            parse_input("00100\n1111"),
+            Err(ParseError::new(2, 1, "expected 5 bits, found 4"))
+        );
     }
 }
@@ -1,110 +1,595 @@
-use std::vec;
+use aoc2021::parse::{blocks, parse_blocks, parse_lines, parse_separated, parse_token, ParseError};
+use itertools::Itertools;
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt, fs,
+    str::FromStr,
+};
 
 struct Input {
     pub draws: Vec<u32>,
     pub boards: Vec<Board>,
 }
 
+/// A square bingo board of any size.
 #[derive(Clone)]
 struct Board {
     pub rows: Vec<Vec<u32>>,
 }
 
 impl Board {
-    fn is_win(&self, draws: &[u32]) -> bool {
-        let row_match = self
-            .rows
-            .iter()
-            .any(|row| row.iter().all(|n| draws.contains(n)));
-        let col_match = (0..self.rows[0].len())
-            .any(|x| (0..self.rows.len()).all(|y| draws.contains(&self.rows[y][x])));
-        return row_match || col_match;
+    fn size(&self) -> usize {
+        self.rows.len()
     }
 
-    fn score(&self, draws: &[u32]) -> u32 {
-        let sum: u32 = self
-            .rows
-            .iter()
-            .flatten()
-            .filter(|n| !draws.contains(n))
-            .sum();
-        sum * draws.last().unwrap()
+    fn number(&self, (y, x): (usize, usize)) -> u32 {
+        self.rows[y][x]
+    }
+
+    /// The numbers of each pattern this board can complete under `rules`.
+    fn pattern_numbers(&self, rules: Rules) -> Vec<HashSet<u32>> {
+        rules
+            .patterns(self.size())
+            .into_iter()
+            .map(|(_, cells)| cells.into_iter().map(|cell| self.number(cell)).collect())
+            .collect()
     }
 }
 
-#[aoc2021::main(04)]
-fn main(input: &str) -> (u32, u32) {
-    let parsed_input = parse_input(input);
-    (part1(&parsed_input), part2(&parsed_input))
+/// A set of cells that can complete a board, such as a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pattern {
+    Row(usize),
+    Column(usize),
+    /// From the top left to the bottom right.
+    Diagonal,
+    /// From the top right to the bottom left.
+    AntiDiagonal,
+    Corners,
+    Card,
+}
+
+/// What it takes for a board to win.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Rules {
+    /// Any row or column, as in the puzzle.
+    #[default]
+    Lines,
+    /// Any row, column or diagonal.
+    Diagonals,
+    /// All four corners.
+    Corners,
+    /// Every number on the board. Scored by the sum of the whole board,
+    /// which is every marked number: the puzzle's sum of unmarked numbers
+    /// would always be 0.
+    FullCard,
+    /// This many rows and columns.
+    NLines(usize),
 }
 
-fn parse_input(input: &str) -> Input {
-    let mut lines = input.lines();
+impl FromStr for Rules {
+    type Err = String;
 
-    let draws: Vec<u32> = lines
-        .next()
-        .unwrap()
-        .split(',')
-        .map(|n| n.parse::<u32>().unwrap())
-        .collect();
-
-    let mut boards: Vec<Board> = vec![];
-    while lines.by_ref().peekable().peek().is_some() {
-        let board_lines = lines.by_ref().take(5);
-
-        let board = Board {
-            rows: board_lines
-                .map(|line| {
-                    line.split_whitespace()
-                        .map(|n| n.parse::<u32>().unwrap())
-                        .collect()
-                })
-                .collect(),
+    fn from_str(s: &str) -> Result<Rules, String> {
+        let error = || {
+            format!(
+                "expected lines, diagonals, corners, full-card or lines:<n>, found {:?}",
+                s
+            )
         };
-        boards.push(board);
+        match s {
+            "lines" => Ok(Rules::Lines),
+            "diagonals" => Ok(Rules::Diagonals),
+            "corners" => Ok(Rules::Corners),
+            "full-card" => Ok(Rules::FullCard),
+            _ => match s.strip_prefix("lines:").map(str::parse) {
+                Some(Ok(n)) if n > 0 => Ok(Rules::NLines(n)),
+                _ => Err(error()),
+            },
+        }
+    }
+}
+
+impl Rules {
+    /// The patterns a board of `size` can complete, with their cells as
+    /// `(row, column)` pairs.
+    fn patterns(self, size: usize) -> Vec<(Pattern, Vec<(usize, usize)>)> {
+        if size == 0 {
+            return vec![];
+        }
+        let row = |y| (Pattern::Row(y), (0..size).map(|x| (y, x)).collect());
+        let column = |x| (Pattern::Column(x), (0..size).map(|y| (y, x)).collect());
+        let lines = (0..size).map(row).chain((0..size).map(column));
+        match self {
+            Rules::Lines | Rules::NLines(_) => lines.collect(),
+            Rules::Diagonals => lines
+                .chain([
+                    (Pattern::Diagonal, (0..size).map(|i| (i, i)).collect()),
+                    (
+                        Pattern::AntiDiagonal,
+                        (0..size).map(|i| (i, size - 1 - i)).collect(),
+                    ),
+                ])
+                .collect(),
+            Rules::Corners => {
+                let last = size - 1;
+                let corners = [(0, 0), (0, last), (last, 0), (last, last)];
+                vec![(Pattern::Corners, corners.into_iter().unique().collect())]
+            }
+            Rules::FullCard => vec![(
+                Pattern::Card,
+                (0..size).cartesian_product(0..size).collect(),
+            )],
+        }
+    }
+
+    /// How many patterns a board must complete to win.
+    fn required(self) -> usize {
+        match self {
+            Rules::NLines(n) => n,
+            _ => 1,
+        }
     }
-    Input { draws, boards }
 }
 
-fn part1(input: &Input) -> u32 {
-    let mut draw_index = 0;
-    let mut winning_board: Option<&Board> = None;
-    while draw_index < input.draws.len() {
-        winning_board = input
-            .boards
+/// The state of a game of bingo on several boards. Every number is indexed
+/// by the cells it appears in and each pattern counts its marked cells, so a
+/// draw only touches the cells holding the number drawn and the patterns
+/// through them.
+struct Game {
+    rules: Rules,
+    /// For each number, the board, row and column of every cell holding it.
+    cells: HashMap<u32, Vec<(usize, usize, usize)>>,
+    size: usize,
+    patterns: Vec<(Pattern, Vec<(usize, usize)>)>,
+    /// The patterns through each cell, row by row.
+    patterns_through: Vec<Vec<usize>>,
+    /// Per board, whether each cell is marked, row by row.
+    marked: Vec<Vec<bool>>,
+    /// Per board, how many cells of each pattern are marked.
+    marked_in_patterns: Vec<Vec<usize>>,
+    completed: Vec<usize>,
+    sums: Vec<u32>,
+    unmarked_sums: Vec<u32>,
+    won: Vec<bool>,
+}
+
+impl Game {
+    /// A game on `boards`, which must all be the same size.
+    fn new(boards: &[Board], rules: Rules) -> Game {
+        let size = boards.first().map_or(0, Board::size);
+        let mut cells = HashMap::<u32, Vec<_>>::new();
+        for (b, board) in boards.iter().enumerate() {
+            assert_eq!(board.size(), size, "boards of differing sizes");
+            for (y, row) in board.rows.iter().enumerate() {
+                for (x, &n) in row.iter().enumerate() {
+                    cells.entry(n).or_default().push((b, y, x));
+                }
+            }
+        }
+        let patterns = rules.patterns(size);
+        let mut patterns_through = vec![vec![]; size * size];
+        for (p, (_, pattern_cells)) in patterns.iter().enumerate() {
+            for &(y, x) in pattern_cells {
+                patterns_through[y * size + x].push(p);
+            }
+        }
+        let sums = boards
             .iter()
-            .find(|board| board.is_win(&input.draws[0..draw_index]));
+            .map(|board| board.rows.iter().flatten().sum())
+            .collect::<Vec<_>>();
+        Game {
+            rules,
+            cells,
+            size,
+            marked: vec![vec![false; size * size]; boards.len()],
+            marked_in_patterns: vec![vec![0; patterns.len()]; boards.len()],
+            patterns,
+            patterns_through,
+            completed: vec![0; boards.len()],
+            unmarked_sums: sums.clone(),
+            sums,
+            won: vec![false; boards.len()],
+        }
+    }
+
+    /// Marks `number` on every board, returning the boards it made win, in
+    /// order, with the pattern that completed each. Boards that have already
+    /// won are still marked but never win again.
+    fn draw(&mut self, number: u32) -> Vec<(usize, Pattern)> {
+        let mut wins = vec![];
+        for &(b, y, x) in self.cells.get(&number).into_iter().flatten() {
+            let marked = &mut self.marked[b][y * self.size + x];
+            if *marked {
+                continue;
+            }
+            *marked = true;
+            self.unmarked_sums[b] -= number;
+
+            for &p in &self.patterns_through[y * self.size + x] {
+                self.marked_in_patterns[b][p] += 1;
+                if self.marked_in_patterns[b][p] < self.patterns[p].1.len() {
+                    continue;
+                }
+                self.completed[b] += 1;
+                if self.completed[b] >= self.rules.required() && !self.won[b] {
+                    self.won[b] = true;
+                    wins.push((b, self.patterns[p].0));
+                }
+            }
+        }
+        wins
+    }
+
+    /// The score of `board` if it won with `number`, using the whole board's
+    /// sum for [`Rules::FullCard`].
+    fn score(&self, board: usize, number: u32) -> u32 {
+        let sum = match self.rules {
+            Rules::FullCard => self.sums[board],
+            _ => self.unmarked_sums[board],
+        };
+        sum * number
+    }
+}
+
+/// When and how a board won.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Win {
+    board: usize,
+    /// The index of the winning draw.
+    round: usize,
+    number: u32,
+    pattern: Pattern,
+    score: u32,
+}
+
+/// Every board's win, in the order they happened, and the boards that never
+/// won.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Timeline {
+    wins: Vec<Win>,
+    never_won: Vec<usize>,
+}
 
-        if winning_board.is_some() {
+fn play(boards: &[Board], draws: &[u32], rules: Rules) -> Timeline {
+    let mut game = Game::new(boards, rules);
+    let mut wins = vec![];
+    for (round, &number) in draws.iter().enumerate() {
+        for (board, pattern) in game.draw(number) {
+            wins.push(Win {
+                board,
+                round,
+                number,
+                pattern,
+                score: game.score(board, number),
+            });
+        }
+        if wins.len() == boards.len() {
             break;
         }
+    }
+    Timeline {
+        never_won: (0..boards.len()).filter(|&b| !game.won[b]).collect(),
+        wins,
+    }
+}
 
-        draw_index += 1;
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pattern::Row(y) => write!(f, "row {}", y + 1),
+            Pattern::Column(x) => write!(f, "column {}", x + 1),
+            Pattern::Diagonal => write!(f, "diagonal"),
+            Pattern::AntiDiagonal => write!(f, "antidiagonal"),
+            Pattern::Corners => write!(f, "corners"),
+            Pattern::Card => write!(f, "card"),
+        }
     }
+}
 
-    winning_board.unwrap().score(&input.draws[0..draw_index])
+impl fmt::Display for Timeline {
+    /// A table of the wins, numbering rounds, boards, rows and columns from 1.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5}  {:>5}  {:>5}  {:<12}  {:>7}",
+            "Round", "Draw", "Board", "Pattern", "Score"
+        )?;
+        for win in &self.wins {
+            writeln!(
+                f,
+                "{:>5}  {:>5}  {:>5}  {:<12}  {:>7}",
+                win.round + 1,
+                win.number,
+                win.board + 1,
+                win.pattern.to_string(),
+                win.score
+            )?;
+        }
+        if !self.never_won.is_empty() {
+            let boards = self.never_won.iter().map(|b| (b + 1).to_string());
+            writeln!(f, "Never won: {}", boards.collect::<Vec<_>>().join(", "))?;
+        }
+        Ok(())
+    }
 }
 
-fn part2(input: &Input) -> u32 {
-    let mut draw_index = 0;
-    let mut remaining_boards = input.boards.clone();
-    while draw_index < input.draws.len() && remaining_boards.len() > 1 {
-        remaining_boards = remaining_boards
-            .into_iter()
-            .filter(|board| !board.is_win(&input.draws[0..draw_index]))
-            .collect();
+#[aoc2021::main(04, tools)]
+fn main(input: &str) -> Result<(u32, u32), Box<dyn Error>> {
+    let parsed_input = parse_input(input)?;
+    Ok((part1(&parsed_input)?, part2(&parsed_input)?))
+}
 
-        draw_index += 1;
+fn parse_board(block: &str, size: usize) -> Result<Board, ParseError> {
+    let rows = parse_lines(block, |line| {
+        let row = line
+            .split_whitespace()
+            .map(|n| parse_token(line, n))
+            .collect::<Result<Vec<u32>, _>>()?;
+        if row.len() != size {
+            return Err(ParseError::at(
+                line,
+                line.trim_start(),
+                format!("expected {} numbers, found {}", size, row.len()),
+            ));
+        }
+        Ok(row)
+    })?;
+    if rows.len() != size {
+        return Err(ParseError::at(
+            block,
+            block,
+            format!("expected {} rows, found {}", size, rows.len()),
+        ));
+    }
+    Ok(Board { rows })
+}
+
+fn parse_input(input: &str) -> Result<Input, ParseError> {
+    let (draws, boards) = input.split_once('\n').unwrap_or((input, ""));
+    // Boards are square, as wide as the first row of the first board.
+    let size = blocks(boards)
+        .next()
+        .and_then(|block| block.lines().next())
+        .map_or(0, |line| line.split_whitespace().count());
+    Ok(Input {
+        draws: parse_separated(draws, ",")?,
+        boards: parse_blocks(boards, |block| parse_board(block, size))
+            .map_err(|e| e.offset_lines(1))?,
+    })
+}
+
+fn part1(input: &Input) -> Result<u32, String> {
+    let timeline = play(&input.boards, &input.draws, Rules::default());
+    let first = timeline.wins.first().ok_or("no board wins")?;
+    Ok(first.score)
+}
+
+/// The score of the board that wins after every other board has, which only
+/// exists if every board wins.
+fn part2(input: &Input) -> Result<u32, String> {
+    let timeline = play(&input.boards, &input.draws, Rules::default());
+    if !timeline.never_won.is_empty() {
+        return Err(format!(
+            "{} of {} boards never win",
+            timeline.never_won.len(),
+            input.boards.len()
+        ));
     }
+    let last = timeline.wins.last().ok_or("no board wins")?;
+    Ok(last.score)
+}
+
+/// Whether a board should be the first or the last to win.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Goal {
+    First,
+    Last,
+}
 
-    assert!(remaining_boards.len() == 1);
+impl FromStr for Goal {
+    type Err = String;
 
-    let last_board = remaining_boards.first().unwrap();
-    while !last_board.is_win(&input.draws[0..draw_index]) {
-        draw_index += 1;
+    fn from_str(s: &str) -> Result<Goal, String> {
+        match s {
+            "first" => Ok(Goal::First),
+            "last" => Ok(Goal::Last),
+            _ => Err(format!("expected first or last, found {:?}", s)),
+        }
+    }
+}
+
+/// Looks for an order in which to draw the numbers of `pool` that makes
+/// `target` win strictly before, or strictly after, every other board wins,
+/// or returns `None` if no order does.
+///
+/// Boards win once `rules.required()` of their patterns are complete, so
+/// rather than listing every combination of patterns that wins, both goals
+/// add one pattern at a time to the numbers drawn, in a depth-first search
+/// that backs out as soon as the wrong board wins. `target` wins first when
+/// some of its patterns win it the game while completing too few of any
+/// other board's. It wins last when the other boards can all be made to win
+/// while it has too few complete, and the pool can still complete it.
+fn adversary(
+    boards: &[Board],
+    target: usize,
+    pool: &[u32],
+    goal: Goal,
+    rules: Rules,
+) -> Option<Vec<u32>> {
+    let pool = pool.iter().copied().unique().collect::<Vec<_>>();
+    let in_pool = pool.iter().copied().collect::<HashSet<_>>();
+    let winnable = |board: &Board| {
+        board
+            .pattern_numbers(rules)
+            .into_iter()
+            .filter(|numbers| numbers.is_subset(&in_pool))
+            .collect::<Vec<_>>()
+    };
+    let required = rules.required();
+    let targets = winnable(&boards[target]);
+    let mut others = (0..boards.len())
+        .filter(|&b| b != target)
+        .map(|b| winnable(&boards[b]))
+        .collect::<Vec<_>>();
+
+    let mut drawn = HashSet::new();
+    match goal {
+        Goal::First => {
+            if !win_alone(&targets, 0, &others, required, &mut drawn) {
+                return None;
+            }
+        }
+        Goal::Last => {
+            if targets.len() < required {
+                return None;
+            }
+            // Boards with fewer patterns first, to prune the search sooner.
+            others.sort_by_key(Vec::len);
+            if !cover(&others, 0, &targets, required, &mut drawn) {
+                return None;
+            }
+        }
     }
+    let mut order = drawn.iter().copied().sorted().collect::<Vec<_>>();
+    order.extend(pool.into_iter().filter(|n| !drawn.contains(n)));
+    Some(order)
+}
 
-    last_board.score(&input.draws[0..draw_index])
+/// How many of `patterns` are complete once `drawn` have been drawn.
+fn completed(patterns: &[HashSet<u32>], drawn: &HashSet<u32>) -> usize {
+    patterns
+        .iter()
+        .filter(|numbers| numbers.is_subset(drawn))
+        .count()
+}
+
+/// Adds the numbers of `targets`' patterns, from the one at `from` on, to
+/// `drawn` until `required` of them are complete, without ever completing
+/// `required` of any of `others`, or returns false if that is impossible.
+fn win_alone(
+    targets: &[HashSet<u32>],
+    from: usize,
+    others: &[Vec<HashSet<u32>>],
+    required: usize,
+    drawn: &mut HashSet<u32>,
+) -> bool {
+    if completed(targets, drawn) >= required {
+        return true;
+    }
+    for (i, numbers) in targets.iter().enumerate().skip(from) {
+        let added = numbers.difference(drawn).copied().collect::<Vec<_>>();
+        if added.is_empty() {
+            continue;
+        }
+        drawn.extend(&added);
+        if others
+            .iter()
+            .all(|other| completed(other, drawn) < required)
+            && win_alone(targets, i + 1, others, required, drawn)
+        {
+            return true;
+        }
+        for n in added {
+            drawn.remove(&n);
+        }
+    }
+    false
+}
+
+/// Adds the numbers of patterns of each of `boards` to `drawn`, trying the
+/// first board's from the one at `from` on, until every board has `required`
+/// complete while `targets` still has fewer, or returns false if that is
+/// impossible.
+fn cover(
+    boards: &[Vec<HashSet<u32>>],
+    from: usize,
+    targets: &[HashSet<u32>],
+    required: usize,
+    drawn: &mut HashSet<u32>,
+) -> bool {
+    let Some((patterns, rest)) = boards.split_first() else {
+        return true;
+    };
+    if completed(patterns, drawn) >= required {
+        return cover(rest, 0, targets, required, drawn);
+    }
+    for (i, numbers) in patterns.iter().enumerate().skip(from) {
+        let added = numbers.difference(drawn).copied().collect::<Vec<_>>();
+        if added.is_empty() {
+            continue;
+        }
+        drawn.extend(&added);
+        if completed(targets, drawn) < required && cover(boards, i + 1, targets, required, drawn) {
+            return true;
+        }
+        for n in added {
+            drawn.remove(&n);
+        }
+    }
+    false
+}
+
+const USAGE: &str = "usage: 04 timeline [<rules>] [--input <path>]
+       04 adversary <first|last> <board> [<rules>] [--input <path>] [--pool <n,n,...>]
+where rules are lines, diagonals, corners, full-card or lines:<n>";
+
+/// `04 timeline` prints when and how every board wins. `04 adversary <goal>
+/// <board>` looks for an order of the draws that makes the board, numbered
+/// from 1, win first or last, or of the numbers given by `--pool`. Both play
+/// by the puzzle's rules unless given others, on the puzzle input or on the
+/// draws and boards in the file given by `--input`.
+fn tools(input: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut path = None;
+    let mut pool = None;
+    let mut positional = vec![];
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        match arg {
+            "--input" => path = Some(args.next().ok_or(USAGE)?),
+            "--pool" => pool = Some(args.next().ok_or(USAGE)?),
+            _ => positional.push(arg),
+        }
+    }
+    let (args, rules) = match positional[..] {
+        ["timeline", rules] | ["adversary", _, _, rules] => {
+            (&positional[..positional.len() - 1], rules.parse()?)
+        }
+        _ => (&positional[..], Rules::default()),
+    };
+    let input = match path {
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?,
+        None => input.to_string(),
+    };
+    let input = parse_input(input.trim_end())?;
+    match (args, pool) {
+        (["timeline"], None) => print!("{}", play(&input.boards, &input.draws, rules)),
+        (["adversary", goal, board], pool) => {
+            let goal = goal.parse()?;
+            let board = match board.parse::<usize>() {
+                Ok(board) if (1..=input.boards.len()).contains(&board) => board - 1,
+                _ => {
+                    return Err(format!(
+                        "expected a board from 1 to {}, found {:?}",
+                        input.boards.len(),
+                        board
+                    )
+                    .into())
+                }
+            };
+            let pool = match pool {
+                Some(pool) => parse_separated(pool, ",")?,
+                None => input.draws.clone(),
+            };
+            match adversary(&input.boards, board, &pool, goal, rules) {
+                Some(order) => println!("{}", order.iter().join(",")),
+                None => println!("Impossible"),
+            }
+        }
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -133,7 +618,7 @@ mod tests {
 
     #[test]
     fn test_parse_input() {
-        let parsed = parse_input(INPUT);
+        let parsed = parse_input(INPUT).unwrap();
 
         assert_eq!(
             parsed.draws,
@@ -177,38 +662,336 @@ mod tests {
         );
     }
 
+    /// The line and score with which `board` first wins given `draws`.
+    fn first_win(board: &Board, draws: &[u32], rules: Rules) -> Option<(Pattern, u32)> {
+        let timeline = play(std::slice::from_ref(board), draws, rules);
+        timeline.wins.first().map(|win| (win.pattern, win.score))
+    }
+
     #[test]
     fn test_board_win_row() {
-        let board = &parse_input(&INPUT).boards[0];
-        assert!(board.is_win(&[6, 10, 2, 1, 3, 18, 5]));
+        let board = &parse_input(INPUT).unwrap().boards[0];
+        assert_eq!(
+            first_win(board, &[6, 10, 2, 1, 3, 18, 5], Rules::default()),
+            Some((Pattern::Row(3), (300 - 45) * 5))
+        );
     }
 
     #[test]
     fn test_board_win_col() {
-        let board = &parse_input(&INPUT).boards[0];
-        assert!(board.is_win(&[0, 24, 8, 7, 5, 9, 19]));
+        let board = &parse_input(INPUT).unwrap().boards[0];
+        assert_eq!(
+            first_win(board, &[0, 24, 8, 7, 5, 9, 19], Rules::default()),
+            Some((Pattern::Column(4), (300 - 72) * 19))
+        );
     }
 
     #[test]
     fn test_board_lose() {
-        let board = &parse_input(&INPUT).boards[0];
-        assert!(!board.is_win(&[6, 28, 10, 30, 320, 23, 325]));
+        let board = &parse_input(INPUT).unwrap().boards[0];
+        assert_eq!(
+            first_win(
+                board,
+                &[6, 28, 10, 30, 320, 23, 325, 6, 6],
+                Rules::default()
+            ),
+            None
+        );
     }
 
     #[test]
     fn test_board_score() {
-        let board = &parse_input(&INPUT).boards[2];
+        let board = &parse_input(INPUT).unwrap().boards[2];
         let draws = [7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24];
-        assert_eq!(board.score(&draws), 4512);
+        assert_eq!(
+            first_win(board, &draws, Rules::default()),
+            Some((Pattern::Row(0), 4512))
+        );
+    }
+
+    #[test]
+    fn wins_and_scores_by_other_rules() {
+        let board = &parse_input("1\n\n1 2 3\n4 5 6\n7 8 9").unwrap().boards[0];
+        assert_eq!(first_win(board, &[1, 5, 9], Rules::default()), None);
+        assert_eq!(
+            first_win(board, &[1, 5, 9], Rules::Diagonals),
+            Some((Pattern::Diagonal, (45 - 15) * 9))
+        );
+        assert_eq!(
+            first_win(board, &[3, 5, 7], Rules::Diagonals),
+            Some((Pattern::AntiDiagonal, (45 - 15) * 7))
+        );
+        assert_eq!(
+            first_win(board, &[1, 3, 5, 7, 9], Rules::Corners),
+            Some((Pattern::Corners, (45 - 25) * 9))
+        );
+        assert_eq!(
+            first_win(board, &[9, 8, 7, 6, 5, 4, 3, 2, 1], Rules::FullCard),
+            Some((Pattern::Card, 45))
+        );
+        assert_eq!(first_win(board, &[1, 2, 3, 4, 7], Rules::NLines(3)), None);
+        assert_eq!(
+            first_win(board, &[1, 2, 3, 4, 7], Rules::NLines(2)),
+            Some((Pattern::Column(0), (45 - 17) * 7))
+        );
+    }
+
+    #[test]
+    fn parses_rules() {
+        assert_eq!("full-card".parse(), Ok(Rules::FullCard));
+        assert_eq!("lines:3".parse(), Ok(Rules::NLines(3)));
+        assert!("lines:0".parse::<Rules>().is_err());
+        assert!("stripes".parse::<Rules>().is_err());
+    }
+
+    #[test]
+    fn plays_boards_of_other_sizes() {
+        let input = "4,5,6,10,11,12
+
+1 2 3
+4 5 6
+7 8 9
+
+10 11 12
+ 4 13 14
+ 5 15  6";
+        let parsed = parse_input(input).unwrap();
+        assert_eq!(parsed.boards[1].size(), 3);
+        assert_eq!(part1(&parsed), Ok((45 - 15) * 6));
+        assert_eq!(part2(&parsed), Ok((90 - 25) * 10));
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(INPUT)), 4512);
+        assert_eq!(part1(&parse_input(INPUT).unwrap()), Ok(4512));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(INPUT)), 1924);
+        assert_eq!(part2(&parse_input(INPUT).unwrap()), Ok(1924));
+    }
+
+    #[test]
+    fn reports_boards_that_never_win() {
+        let mut input = parse_input(INPUT).unwrap();
+        input.boards.push(Board {
+            rows: vec![vec![99; 5]; 5],
+        });
+        assert_eq!(part1(&input), Ok(4512));
+        assert_eq!(part2(&input), Err("1 of 4 boards never win".to_string()));
+        input.draws.clear();
+        assert_eq!(part1(&input), Err("no board wins".to_string()));
+    }
+
+    #[test]
+    fn records_the_timeline() {
+        let mut input = parse_input(INPUT).unwrap();
+        input.boards.push(Board {
+            rows: vec![vec![99; 5]; 5],
+        });
+        let timeline = play(&input.boards, &input.draws, Rules::default());
+        assert_eq!(
+            timeline.wins[0],
+            Win {
+                board: 2,
+                round: 11,
+                number: 24,
+                pattern: Pattern::Row(0),
+                score: 4512
+            }
+        );
+        assert_eq!(
+            timeline
+                .wins
+                .iter()
+                .map(|win| win.board)
+                .collect::<Vec<_>>(),
+            [2, 0, 1]
+        );
+        assert_eq!(timeline.never_won, [3]);
+        assert_eq!(
+            timeline.to_string(),
+            "\
+Round   Draw  Board  Pattern         Score
+   12     24      3  row 1            4512
+   14     16      1  row 3            2192
+   15     13      2  column 3         1924
+Never won: 4
+"
+        );
+    }
+
+    /// Checks that `order` makes `target` win alone, first or last.
+    fn wins_as(boards: &[Board], target: usize, order: &[u32], goal: Goal, rules: Rules) -> bool {
+        let wins = play(boards, order, rules).wins;
+        let (win, others) = match goal {
+            Goal::First => (wins.first(), wins.get(1)),
+            Goal::Last if wins.len() == boards.len() => {
+                (wins.last(), wins.len().checked_sub(2).map(|i| &wins[i]))
+            }
+            Goal::Last => return false,
+        };
+        win.is_some_and(|win| {
+            win.board == target && others.is_none_or(|other| other.round != win.round)
+        })
+    }
+
+    #[test]
+    fn finds_adversarial_orders() {
+        let input = parse_input(INPUT).unwrap();
+        for target in 0..input.boards.len() {
+            for goal in [Goal::First, Goal::Last] {
+                let order =
+                    adversary(&input.boards, target, &input.draws, goal, Rules::default()).unwrap();
+                assert!(
+                    wins_as(&input.boards, target, &order, goal, Rules::default()),
+                    "board {} {:?} with {:?}",
+                    target,
+                    goal,
+                    order
+                );
+                assert_eq!(
+                    order.iter().sorted().collect::<Vec<_>>(),
+                    input.draws.iter().sorted().collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn finds_adversarial_orders_under_other_rules() {
+        let input = parse_input(INPUT).unwrap();
+        for rules in [
+            Rules::Diagonals,
+            Rules::Corners,
+            Rules::FullCard,
+            Rules::NLines(2),
+        ] {
+            for goal in [Goal::First, Goal::Last] {
+                let orders = (0..input.boards.len())
+                    .filter_map(|target| {
+                        let order = adversary(&input.boards, target, &input.draws, goal, rules)?;
+                        Some((target, order))
+                    })
+                    .collect::<Vec<_>>();
+                assert!(!orders.is_empty(), "{:?} {:?}", rules, goal);
+                for (target, order) in orders {
+                    assert!(
+                        wins_as(&input.boards, target, &order, goal, rules),
+                        "board {} {:?} by {:?} with {:?}",
+                        target,
+                        goal,
+                        rules,
+                        order
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn finds_adversarial_orders_needing_many_lines() {
+        // Listing every way to complete 8 of these boards' 20 lines would
+        // mean over 125,000 sets per board.
+        let boards = (0..3)
+            .map(|b| Board {
+                rows: (0..10)
+                    .map(|y| (0..10).map(|x| (y * 10 + x * (b + 1) * 3) % 100).collect())
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        let pool = (0..100).collect::<Vec<_>>();
+        for goal in [Goal::First, Goal::Last] {
+            let order = adversary(&boards, 1, &pool, goal, Rules::NLines(8)).unwrap();
+            assert!(wins_as(&boards, 1, &order, goal, Rules::NLines(8)));
+        }
+    }
+
+    #[test]
+    fn proves_adversarial_orders_impossible() {
+        let board = |rows: [[u32; 2]; 2]| Board {
+            rows: rows.map(Vec::from).to_vec(),
+        };
+        let pool = [1, 2, 3, 4, 5, 6, 7, 8];
+
+        // Identical boards always win together.
+        let twins = [board([[1, 2], [3, 4]]), board([[4, 3], [2, 1]])];
+        assert_eq!(
+            adversary(&twins, 0, &pool, Goal::First, Rules::default()),
+            None
+        );
+        assert_eq!(
+            adversary(&twins, 0, &pool, Goal::Last, Rules::default()),
+            None
+        );
+
+        // Every line of the second board holds a line of another board, and
+        // the first board only wins once 1 and 2 are drawn.
+        let boards = [
+            board([[1, 2], [2, 1]]),
+            board([[1, 2], [3, 4]]),
+            board([[3, 4], [9, 10]]),
+            board([[1, 11], [3, 12]]),
+            board([[2, 13], [4, 14]]),
+        ];
+        let pool = (1..=14).collect::<Vec<_>>();
+        assert_eq!(
+            adversary(&boards, 1, &pool, Goal::First, Rules::default()),
+            None
+        );
+        assert_eq!(
+            adversary(&boards, 1, &pool, Goal::Last, Rules::default()),
+            None
+        );
+        let order = adversary(&boards, 2, &pool, Goal::Last, Rules::default()).unwrap();
+        assert!(wins_as(&boards, 2, &order, Goal::Last, Rules::default()));
+
+        // Numbers missing from the pool can never be drawn.
+        assert_eq!(
+            adversary(&boards, 2, &[9, 10], Goal::Last, Rules::default()),
+            None
+        );
+        assert_eq!(
+            adversary(&boards, 2, &[9, 10], Goal::First, Rules::default()),
+            Some(vec![9, 10])
+        );
+    }
+
+    #[test]
+    fn rejects_bad_draws() {
+        assert_eq!(
+            parse_input("7,4,x,5").err(),
+            Some(ParseError::new(1, 5, "expected u32, found \"x\""))
+        );
+    }
+
+    #[test]
+    fn rejects_short_boards() {
+        let input = "7,4,9
+
+22 13 17 11  0
+8  2 23  4 24
+21  9 14 16  7
+
+3 15  0  2 22";
+        assert_eq!(
+            parse_input(input).err(),
+            Some(ParseError::new(3, 1, "expected 5 rows, found 3"))
+        );
+    }
+
+    #[test]
+    fn rejects_short_rows() {
+        let input = "7,4,9
+
+22 13 17 11  0
+8  2 23  4 24
+21  9 14 16
+6 10  3 18  5
+1 12 20 15 19";
+        assert_eq!(
+            parse_input(input).err(),
+            Some(ParseError::new(5, 1, "expected 5 numbers, found 4"))
+        );
     }
 }
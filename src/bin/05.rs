@@ -1,156 +1,489 @@
-use std::{collections::HashMap, hash::Hash, vec};
+use aoc2021::{
+    parse::{parse_lines, scan, ParseError},
+    point::Point,
+    raster::Raster,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    fs::File,
+    io::{self, BufWriter, Write},
+    str::FromStr,
+};
 
 struct Input {
     pub lines: Vec<Line>,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-struct Point {
-    pub x: i32,
-    pub y: i32,
-}
-
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 struct Line {
-    pub start: Point,
-    pub end: Point,
+    pub start: Point<i32>,
+    pub end: Point<i32>,
 }
 
-#[aoc2021::main(05)]
-fn main(input: &str) -> (u32, u32) {
-    let parsed_input = parse_input(input);
-    (part1(&parsed_input), part2(&parsed_input))
+#[aoc2021::main(05, tools)]
+fn main(input: &str) -> Result<(u32, u32), ParseError> {
+    let parsed_input = parse_input(input)?;
+    Ok((part1(&parsed_input), part2(&parsed_input)))
 }
 
-fn input_line_to_points(line: &str) -> Line {
-    let mut split = line.split("->");
-
-    let start = split
-        .next()
-        .unwrap()
-        .trim()
-        .split(",")
-        .map(|s| s.parse::<i32>().unwrap())
-        .collect::<Vec<i32>>();
-
-    let end = split
-        .next()
-        .unwrap()
-        .trim()
-        .split(",")
-        .map(|s| s.parse::<i32>().unwrap())
-        .collect::<Vec<i32>>();
-
-    Line {
-        start: Point {
-            x: start[0],
-            y: start[1],
-        },
-        end: Point {
-            x: end[0],
-            y: end[1],
-        },
-    }
+fn input_line_to_points(line: &str) -> Result<Line, ParseError> {
+    let [x1, y1, x2, y2] = scan(line, "{},{} -> {},{}")?;
+    Ok(Line {
+        start: Point { x: x1, y: y1 },
+        end: Point { x: x2, y: y2 },
+    })
 }
 
-fn parse_input(input: &str) -> Input {
-    Input {
-        lines: input.lines().map(input_line_to_points).collect(),
-    }
+fn parse_input(input: &str) -> Result<Input, ParseError> {
+    Ok(Input {
+        lines: parse_lines(input, input_line_to_points)?,
+    })
 }
 
-// generated by copilot
-fn points_on_line(line: &Line) -> Vec<Point> {
-    let mut points = vec![];
+fn points_on_line(line: &Line) -> Raster<i32> {
+    Raster::new(line.start, line.end)
+}
 
-    let mut x = line.start.x;
-    let mut y = line.start.y;
+fn line_is_horizontal(line: &Line) -> bool {
+    line.start.y == line.end.y
+}
 
-    let dx = line.end.x - line.start.x;
-    let dy = line.end.y - line.start.y;
+fn line_is_vertical(line: &Line) -> bool {
+    line.start.x == line.end.x
+}
 
-    let mut x_inc = 1;
-    let mut y_inc = 1;
+/// The directions a straight vent line can run in. Every cell of a line in
+/// a family has the same `key`, and cells are ordered along it by `position`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
 
-    if dx < 0 {
-        x_inc = -1;
+impl Family {
+    const ALL: [Family; 4] = [
+        Family::Horizontal,
+        Family::Vertical,
+        Family::Diagonal,
+        Family::AntiDiagonal,
+    ];
+
+    /// The family of the line from `start` to `end`, taken as `i64`s so that
+    /// lines spanning more than an `i32` can hold are still classified.
+    fn of(start: Point<i64>, end: Point<i64>) -> Option<Family> {
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        if dy == 0 {
+            Some(Family::Horizontal)
+        } else if dx == 0 {
+            Some(Family::Vertical)
+        } else if dx == dy {
+            Some(Family::Diagonal)
+        } else if dx == -dy {
+            Some(Family::AntiDiagonal)
+        } else {
+            None
+        }
     }
 
-    if dy < 0 {
-        y_inc = -1;
+    /// The `(a, b)` for which `a * x + b * y` is the same along every line.
+    fn normal(self) -> (i64, i64) {
+        match self {
+            Family::Horizontal => (0, 1),
+            Family::Vertical => (1, 0),
+            Family::Diagonal => (1, -1),
+            Family::AntiDiagonal => (1, 1),
+        }
     }
 
-    let dx = dx.abs();
-    let dy = dy.abs();
+    /// The key of the line through `p`, and how far along it `p` is.
+    fn locate(self, p: Point<i64>) -> (i64, i64) {
+        let (a, b) = self.normal();
+        let position = if self == Family::Vertical { p.y } else { p.x };
+        (a * p.x + b * p.y, position)
+    }
 
-    let mut err = dx - dy;
+    /// The cell where the line `key` of this family meets the line `other_key`
+    /// of `other`, if they meet on a cell at all.
+    fn crossing(self, key: i64, other: Family, other_key: i64) -> Option<Point<i64>> {
+        let ((a1, b1), (a2, b2)) = (self.normal(), other.normal());
+        let det = a1 * b2 - a2 * b1;
+        if det == 0 {
+            return None;
+        }
+        let (x, y) = (key * b2 - other_key * b1, a1 * other_key - a2 * key);
+        (x % det == 0 && y % det == 0).then(|| Point::new(x / det, y / det))
+    }
+}
 
-    loop {
-        points.push(Point { x, y });
+/// The cells of one line of a family covered by at least one and by at least
+/// two of its segments, as sorted, disjoint, inclusive ranges of positions.
+#[derive(Debug, Default)]
+struct Coverage {
+    once: Vec<(i64, i64)>,
+    twice: Vec<(i64, i64)>,
+}
 
-        if x == line.end.x && y == line.end.y {
-            break;
+impl Coverage {
+    /// Sweeps over the starts and ends of `segments`, tracking how many of
+    /// them cover the cells between one event and the next.
+    fn sweep(segments: &[(i64, i64)]) -> Coverage {
+        let mut events = segments
+            .iter()
+            .flat_map(|&(lo, hi)| [(lo, 1), (hi + 1, -1)])
+            .collect::<Vec<_>>();
+        events.sort_unstable();
+
+        let mut coverage = Coverage::default();
+        let mut depth = 0;
+        let (mut once_start, mut twice_start) = (0, 0);
+        for (position, change) in events {
+            let before = depth;
+            depth += change;
+            match (before, depth) {
+                (0, 1) => once_start = position,
+                (1, 0) => coverage.once.push((once_start, position - 1)),
+                (1, 2) => twice_start = position,
+                (2, 1) => coverage.twice.push((twice_start, position - 1)),
+                _ => {}
+            }
         }
+        coverage
+    }
 
-        let e2 = 2 * err;
+    fn covers_once(&self, position: i64) -> bool {
+        Coverage::contains(&self.once, position)
+    }
 
-        if e2 > -dy {
-            err -= dy;
-            x += x_inc;
-        }
+    fn covers_twice(&self, position: i64) -> bool {
+        Coverage::contains(&self.twice, position)
+    }
 
-        if e2 < dx {
-            err += dx;
-            y += y_inc;
+    fn contains(ranges: &[(i64, i64)], position: i64) -> bool {
+        let i = ranges.partition_point(|&(_, hi)| hi < position);
+        ranges.get(i).is_some_and(|&(lo, _)| lo <= position)
+    }
+}
+
+/// Counts the cells covered by at least two of `lines` without visiting
+/// every cell, so that lines may run for millions of cells. Overlaps between
+/// lines running the same way are found by sweeping along each of their
+/// shared lines; lines running different ways meet in at most one cell each.
+/// Lines that are not horizontal, vertical or at 45° are split into their
+/// cells, each a horizontal line of its own.
+fn count_overlaps<'a>(lines: impl IntoIterator<Item = &'a Line>) -> usize {
+    let mut segments = Family::ALL.map(|_| BTreeMap::<i64, Vec<(i64, i64)>>::new());
+    let mut add = |family: Family, start: Point<i64>, end: Point<i64>| {
+        let (key, a) = family.locate(start);
+        let (_, b) = family.locate(end);
+        segments[family as usize]
+            .entry(key)
+            .or_default()
+            .push((a.min(b), a.max(b)));
+    };
+    for line in lines {
+        let (start, end) = (widen(line.start), widen(line.end));
+        match Family::of(start, end) {
+            Some(family) => add(family, start, end),
+            None => {
+                for p in Raster::new(start, end) {
+                    add(Family::Horizontal, p, p);
+                }
+            }
         }
     }
+    let coverage = segments.map(|lines| {
+        lines
+            .into_iter()
+            .map(|(key, segments)| (key, Coverage::sweep(&segments)))
+            .collect::<BTreeMap<_, _>>()
+    });
+
+    let covered_twice = |family: Family, p: Point<i64>| {
+        let (key, position) = family.locate(p);
+        coverage[family as usize]
+            .get(&key)
+            .is_some_and(|coverage| coverage.covers_twice(position))
+    };
+    let mut count = coverage
+        .iter()
+        .flat_map(|lines| lines.values())
+        .flat_map(|coverage| &coverage.twice)
+        .map(|&(lo, hi)| (hi - lo + 1) as usize)
+        .sum::<usize>();
+
+    // Cells where lines of different families cross. Each is covered at least
+    // twice, but may already have been counted once for every family that
+    // covers it twice by itself.
+    let mut crossings = HashSet::new();
+    for (i, &first) in Family::ALL.iter().enumerate() {
+        for &second in &Family::ALL[i + 1..] {
+            for (&key, first_coverage) in &coverage[first as usize] {
+                for (&other_key, second_coverage) in &coverage[second as usize] {
+                    let Some(p) = first.crossing(key, second, other_key) else {
+                        continue;
+                    };
+                    if first_coverage.covers_once(first.locate(p).1)
+                        && second_coverage.covers_once(second.locate(p).1)
+                    {
+                        crossings.insert(p);
+                    }
+                }
+            }
+        }
+    }
+    for p in crossings {
+        let counted = Family::ALL
+            .into_iter()
+            .filter(|&family| covered_twice(family, p))
+            .count();
+        count = count + 1 - counted;
+    }
+    count
+}
 
-    points
+fn widen(p: Point<i32>) -> Point<i64> {
+    Point::new(p.x as i64, p.y as i64)
 }
 
-fn line_is_horizontal(line: &Line) -> bool {
-    line.start.y == line.end.y
+/// Which of the vent lines to consider: part one only looks at the
+/// horizontal and vertical ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Selection {
+    Straight,
+    All,
 }
 
-fn line_is_vertical(line: &Line) -> bool {
-    line.start.x == line.end.x
+impl FromStr for Selection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Selection, String> {
+        match s {
+            "straight" => Ok(Selection::Straight),
+            "all" => Ok(Selection::All),
+            _ => Err(format!("expected straight or all, found {:?}", s)),
+        }
+    }
 }
 
-fn part1(input: &Input) -> u32 {
-    let points_map = input
-        .lines
-        .iter()
-        .filter(|line| line_is_horizontal(line) || line_is_vertical(line))
-        .map(|l| points_on_line(&l))
-        .fold(HashMap::new(), |mut acc: HashMap<Point, u32>, points| {
-            for point in points {
-                let counter = acc.entry(point).or_insert(0);
-                *counter += 1;
-            }
-            acc
-        });
+impl Selection {
+    fn lines(self, input: &Input) -> impl Iterator<Item = &Line> {
+        input.lines.iter().filter(move |line| {
+            self == Selection::All || line_is_horizontal(line) || line_is_vertical(line)
+        })
+    }
+}
 
-    points_map.values().filter(|v| **v > 1).count() as u32
+fn part1(input: &Input) -> u32 {
+    count_overlaps(Selection::Straight.lines(input)) as u32
 }
 
 fn part2(input: &Input) -> u32 {
-    let points_map = input.lines.iter().map(|l| points_on_line(&l)).fold(
-        HashMap::new(),
-        |mut acc: HashMap<Point, u32>, points| {
-            for point in points {
-                let counter = acc.entry(point).or_insert(0);
-                *counter += 1;
+    count_overlaps(Selection::All.lines(input)) as u32
+}
+
+/// How many lines cover each cell of the box from the origin, or the
+/// top-left-most point of any line if that is further out, to the
+/// bottom-right-most point of any line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Diagram {
+    min: Point<i32>,
+    width: usize,
+    height: usize,
+    counts: Vec<u32>,
+}
+
+impl Diagram {
+    /// Counts the cells covered by `lines`, refusing diagrams wider or taller
+    /// than `limit` cells rather than allocating them.
+    fn new<'a>(lines: impl IntoIterator<Item = &'a Line>, limit: usize) -> Result<Diagram, String> {
+        let lines = lines.into_iter().collect::<Vec<_>>();
+        let ends = lines.iter().flat_map(|line| [line.start, line.end]);
+        let (min, max) = ends.fold((Point::new(0, 0), Point::new(0, 0)), |(min, max), p| {
+            (
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        });
+        let (min, max) = (widen(min), widen(max));
+        let span = |lo: i64, hi: i64| usize::try_from(hi - lo + 1).ok().filter(|&n| n <= limit);
+        let (Some(width), Some(height)) = (span(min.x, max.x), span(min.y, max.y)) else {
+            return Err(format!(
+                "a diagram of {}x{} cells is larger than the limit of {}x{}",
+                max.x - min.x + 1,
+                max.y - min.y + 1,
+                limit,
+                limit
+            ));
+        };
+        let mut counts = vec![0; width * height];
+        for p in lines.into_iter().flat_map(points_on_line).map(widen) {
+            counts[(p.y - min.y) as usize * width + (p.x - min.x) as usize] += 1;
+        }
+        Ok(Diagram {
+            min: Point::new(min.x as i32, min.y as i32),
+            width,
+            height,
+            counts,
+        })
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[u32]> {
+        self.counts.chunks(self.width)
+    }
+
+    fn max(&self) -> u32 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    /// The diagram as the puzzle draws it: `.` for cells no line covers and
+    /// the number of lines otherwise, with `+` standing in for 10 or more.
+    fn to_ascii(&self) -> String {
+        let mut diagram = String::new();
+        for row in self.rows() {
+            diagram.extend(row.iter().map(|&n| match n {
+                0 => '.',
+                1..=9 => char::from_digit(n, 10).unwrap(),
+                _ => '+',
+            }));
+            diagram.push('\n');
+        }
+        diagram
+    }
+
+    /// The colour of a cell covered by each number of lines, fading from
+    /// black through red and yellow to white for the most covered cells.
+    fn palette(&self) -> Vec<[u8; 3]> {
+        let max = self.max().max(1) as f64;
+        (0..=self.max())
+            .map(|n| {
+                let t = n as f64 / max;
+                let channel = |from: f64| ((3.0 * t - from).clamp(0.0, 1.0) * 255.0).round() as u8;
+                [channel(0.0), channel(1.0), channel(2.0)]
+            })
+            .collect()
+    }
+
+    /// Writes the diagram as a binary PPM image with one pixel per cell.
+    fn write_ppm(&self, out: &mut impl Write) -> io::Result<()> {
+        let heat = self.palette();
+        writeln!(out, "P6\n{} {}\n255", self.width, self.height)?;
+        for row in self.rows() {
+            out.write_all(
+                &row.iter()
+                    .flat_map(|&n| heat[n as usize])
+                    .collect::<Vec<_>>(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the diagram as an SVG image with a unit square per covered
+    /// cell, and runs of equally covered cells in a row merged into one
+    /// rectangle.
+    fn write_svg(&self, out: &mut impl Write) -> io::Result<()> {
+        let heat = self.palette();
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" \
+             shape-rendering=\"crispEdges\">",
+            self.min.x, self.min.y, self.width, self.height
+        )?;
+        writeln!(
+            out,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#000\"/>",
+            self.min.x, self.min.y, self.width, self.height
+        )?;
+        for (y, row) in self.rows().enumerate() {
+            let mut x = 0;
+            for run in row.chunk_by(|a, b| a == b) {
+                let (n, length) = (run[0], run.len());
+                if n > 0 {
+                    let [r, g, b] = heat[n as usize];
+                    writeln!(
+                        out,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"1\" \
+                         fill=\"#{:02x}{:02x}{:02x}\"><title>{}</title></rect>",
+                        self.min.x + x as i32,
+                        self.min.y + y as i32,
+                        length,
+                        r,
+                        g,
+                        b,
+                        n
+                    )?;
+                }
+                x += length;
             }
-            acc
-        },
-    );
+        }
+        writeln!(out, "</svg>")
+    }
+}
 
-    points_map.values().filter(|v| **v > 1).count() as u32
+const USAGE: &str = "usage: 05 diagram <straight|all>
+       05 <ppm|svg> <straight|all> <path>";
+
+/// The widest or tallest diagram `05 diagram` will print.
+const DIAGRAM_LIMIT: usize = 200;
+
+/// The widest or tallest image `05 ppm` and `05 svg` will draw.
+const IMAGE_LIMIT: usize = 4096;
+
+/// `05 diagram <lines>` prints how many of the lines cover each cell, as
+/// the puzzle draws it, considering only horizontal and vertical lines for
+/// `straight`. `05 ppm <lines> <path>` and `05 svg <lines> <path>` write the
+/// same map to `path` as a heatmap image, for inputs too large to print.
+fn tools(input: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let (tool, selection, path) = match args[..] {
+        ["diagram", selection] => ("diagram", selection, None),
+        [tool @ ("ppm" | "svg"), selection, path] => (tool, selection, Some(path)),
+        _ => return Err(USAGE.into()),
+    };
+    let selection = selection.parse::<Selection>()?;
+    let input = parse_input(input)?;
+    let lines = selection.lines(&input);
+
+    let Some(path) = path else {
+        let diagram =
+            Diagram::new(lines, DIAGRAM_LIMIT).map_err(|e| format!("{}; try ppm or svg", e))?;
+        print!("{}", diagram.to_ascii());
+        return Ok(());
+    };
+    let diagram = Diagram::new(lines, IMAGE_LIMIT)?;
+    // Written as it is drawn, as large images run to tens of megabytes.
+    let write = || -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        match tool {
+            "ppm" => diagram.write_ppm(&mut out)?,
+            _ => diagram.write_svg(&mut out)?,
+        }
+        out.flush()
+    };
+    write().map_err(|e| format!("{}: {}", path, e))?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc2021::counter::Counter;
+    use itertools::Itertools;
+
+    const DIAGRAM: [&str; 10] = [
+        "1.1....11.",
+        ".111...2..",
+        "..2.1.111.",
+        "...1.2.2..",
+        ".112313211",
+        "...1.2....",
+        "..1...1...",
+        ".1.....1..",
+        "1.......1.",
+        "222111....",
+    ];
 
     const INPUT: &str = "0,9 -> 5,9
 8,0 -> 0,8
@@ -166,7 +499,7 @@ mod tests {
     #[test]
     fn test_line_to_points() {
         assert_eq!(
-            input_line_to_points("0,9 -> 5,9"),
+            input_line_to_points("0,9 -> 5,9").unwrap(),
             Line {
                 start: Point { x: 0, y: 9 },
                 end: Point { x: 5, y: 9 }
@@ -176,7 +509,7 @@ mod tests {
 
     #[test]
     fn test_parse_input() {
-        let parsed = parse_input(INPUT);
+        let parsed = parse_input(INPUT).unwrap();
         assert_eq!(
             parsed.lines[0],
             Line {
@@ -255,7 +588,7 @@ mod tests {
             start: Point { x: 0, y: 0 },
             end: Point { x: 3, y: 0 },
         };
-        let points = points_on_line(&line);
+        let points = points_on_line(&line).collect::<Vec<_>>();
         assert_eq!(
             points,
             vec![
@@ -273,7 +606,7 @@ mod tests {
             start: Point { x: 0, y: 0 },
             end: Point { x: 0, y: 3 },
         };
-        let points = points_on_line(&line);
+        let points = points_on_line(&line).collect::<Vec<_>>();
         assert_eq!(
             points,
             vec![
@@ -287,11 +620,178 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(INPUT)), 5);
+        assert_eq!(part1(&parse_input(INPUT).unwrap()), 5);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(INPUT)), 12);
+        assert_eq!(part2(&parse_input(INPUT).unwrap()), 12);
+    }
+
+    fn count_by_point(lines: &[Line]) -> usize {
+        lines
+            .iter()
+            .flat_map(points_on_line)
+            .collect::<Counter<_>>()
+            .count_more_than(1)
+    }
+
+    fn line((x1, y1): (i32, i32), (x2, y2): (i32, i32)) -> Line {
+        Line {
+            start: Point::new(x1, y1),
+            end: Point::new(x2, y2),
+        }
+    }
+
+    #[test]
+    fn sweeps_the_same_overlaps_as_counting_points() {
+        // Lines of every family and a few steeper ones, crowded into a small
+        // grid so that they overlap and cross in every combination.
+        let mut seed = 5u32;
+        let mut next = |n: u32| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            ((seed >> 16) % n) as i32
+        };
+        for _ in 0..50 {
+            let lines = (0..40)
+                .map(|_| {
+                    let start = (next(20), next(20));
+                    let length = next(15);
+                    let end = match next(5) {
+                        0 => (start.0 + length, start.1),
+                        1 => (start.0, start.1 - length),
+                        2 => (start.0 - length, start.1 - length),
+                        3 => (start.0 + length, start.1 - length),
+                        _ => (next(20), next(20)),
+                    };
+                    line(start, end)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                count_overlaps(&lines),
+                count_by_point(&lines),
+                "{:?}",
+                lines
+            );
+        }
+    }
+
+    #[test]
+    fn sweeps_lines_millions_of_cells_long() {
+        let far = 4_000_000;
+        let lines = [
+            line((0, 0), (far, 0)),
+            line((far / 2, 0), (far * 2, 0)),
+            line((1, -far), (1, far)),
+            line((-far, -far), (far, far)),
+            line((far, -far), (-far, far)),
+        ];
+        // The horizontal overlap, the vertical line crossing the first
+        // horizontal line and both diagonals, and the origin, where the
+        // diagonals cross each other and the horizontal lines.
+        assert_eq!(count_overlaps(&lines), (far / 2 + 1) as usize + 4);
+    }
+
+    #[test]
+    fn sweeps_lines_longer_than_an_i32() {
+        let far = 2_000_000_000;
+        let lines = [
+            line((-far, 0), (far, 0)),
+            line((far - 9, 0), (far, 0)),
+            line((0, -far), (0, far)),
+            line((-far, -far), (far, far)),
+            line((far, -far), (-far, far)),
+            line((5, 1), (8, 2)),
+            line((6, 1), (8, 1)),
+        ];
+        // The end of the long horizontal line, the origin where every long
+        // line crosses, and the cells the last two lines share, which are
+        // clear of the long ones.
+        assert_eq!(count_by_point(&lines[5..]), 1);
+        assert_eq!(count_overlaps(&lines), 10 + 1 + 1);
+    }
+
+    #[test]
+    fn draws_the_puzzle_diagrams() {
+        let input = parse_input(INPUT).unwrap();
+        let straight = Diagram::new(Selection::Straight.lines(&input), 10)
+            .unwrap()
+            .to_ascii();
+        assert_eq!(
+            straight.lines().collect::<Vec<_>>(),
+            [
+                ".......1..",
+                "..1....1..",
+                "..1....1..",
+                ".......1..",
+                ".112111211",
+                "..........",
+                "..........",
+                "..........",
+                "..........",
+                "222111....",
+            ]
+        );
+        let all = Diagram::new(Selection::All.lines(&input), 10)
+            .unwrap()
+            .to_ascii();
+        assert_eq!(all.lines().collect::<Vec<_>>(), DIAGRAM);
+    }
+
+    #[test]
+    fn renders_heatmaps() {
+        let input = parse_input(INPUT).unwrap();
+        let diagram = Diagram::new(Selection::All.lines(&input), 10).unwrap();
+        let heat = diagram.palette();
+        assert_eq!(heat[0], [0, 0, 0]);
+        assert_eq!(heat[diagram.max() as usize], [255, 255, 255]);
+
+        let mut ppm = vec![];
+        diagram.write_ppm(&mut ppm).unwrap();
+        let header = b"P6\n10 10\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 10 * 10 * 3);
+        // The first cell of the first row is covered once, the second not at all.
+        assert_eq!(ppm[header.len()..][..6], [heat[1], [0; 3]].concat());
+
+        let mut svg = vec![];
+        diagram.write_svg(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let cells = svg.matches("<title>").count();
+        let runs = DIAGRAM
+            .iter()
+            .map(|row| row.chars().dedup().filter(|&c| c != '.').count())
+            .sum::<usize>();
+        assert_eq!(cells, runs);
+        assert!(svg.contains("<rect x=\"1\" y=\"1\" width=\"3\" height=\"1\""));
+    }
+
+    #[test]
+    fn refuses_oversized_diagrams() {
+        let input = parse_input(INPUT).unwrap();
+        assert_eq!(
+            Diagram::new(Selection::All.lines(&input), 9),
+            Err("a diagram of 10x10 cells is larger than the limit of 9x9".to_string())
+        );
+        let far = [line((i32::MIN, 0), (i32::MAX, 0))];
+        assert_eq!(
+            Diagram::new(&far, IMAGE_LIMIT),
+            Err(format!(
+                "a diagram of 4294967296x1 cells is larger than the limit of {0}x{0}",
+                IMAGE_LIMIT
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert_eq!(
+            parse_input("0,9 -> 5,9\n8,0 => 0,8").err(),
+            Some(ParseError::new(2, 3, "expected \" -> \" after here"))
+        );
+        assert_eq!(
+            parse_input("0,9 -> 5,-").err(),
+            Some(ParseError::new(1, 10, "expected i32, found \"-\""))
+        );
     }
 }
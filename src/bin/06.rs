@@ -1,50 +1,59 @@
+use aoc2021::{
+    counter::DenseCounter,
+    parse::{parse_token, ParseError},
+};
+
 #[aoc2021::main(06)]
-fn main(input: &str) -> (u64, u64) {
-    let parsed_input = parse_input(input);
-    (part1(&parsed_input), part2(&parsed_input))
+fn main(input: &str) -> Result<(u64, u64), ParseError> {
+    let parsed_input = parse_input(input)?;
+    Ok((part1(&parsed_input), part2(&parsed_input)))
 }
 
-fn parse_input(input: &str) -> Vec<u64> {
+fn parse_input(input: &str) -> Result<Vec<u64>, ParseError> {
     input
-        .split(",")
-        .map(|s| s.parse::<u64>().unwrap())
+        .split(',')
+        .map(|s| {
+            let timer = parse_token(input, s)?;
+            if timer > 8 {
+                return Err(ParseError::at(
+                    input,
+                    s.trim(),
+                    format!("expected a timer from 0 to 8, found {}", timer),
+                ));
+            }
+            Ok(timer)
+        })
         .collect()
 }
 
-fn advance_day(fishes: &mut Vec<u64>) {
-    let mut new_fishes = vec![0; 9];
-    new_fishes[6] = fishes[0];
-    new_fishes[8] = fishes[0];
-    for i in 1..fishes.len() {
-        new_fishes[i - 1] += fishes[i];
-    }
-    *fishes = new_fishes;
+const TIMERS: usize = 9;
+
+fn advance_day(fishes: &mut DenseCounter<TIMERS>) {
+    // Every timer counts down, and fish at 0 both reset to 6 and spawn a new
+    // fish at 8.
+    fishes.rotate_left(1);
+    fishes.add_n(6, fishes[8]);
 }
 
-fn part1(input: &Vec<u64>) -> u64 {
-    let mut fishes: Vec<u64> = vec![0; 9];
-    for i in input {
-        fishes[*i as usize] += 1;
-    }
+fn fish_after(input: &[u64], days: usize) -> u64 {
+    let mut fishes = input
+        .iter()
+        .map(|&timer| timer as usize)
+        .collect::<DenseCounter<TIMERS>>();
 
-    for _ in 0..80 {
+    for _ in 0..days {
         advance_day(&mut fishes);
     }
 
-    return fishes.iter().sum();
+    fishes.total() as u64
 }
 
-fn part2(input: &Vec<u64>) -> u64 {
-    let mut fishes: Vec<u64> = vec![0; 9];
-    for i in input {
-        fishes[*i as usize] += 1;
-    }
-
-    for _ in 0..256 {
-        advance_day(&mut fishes);
-    }
+fn part1(input: &[u64]) -> u64 {
+    fish_after(input, 80)
+}
 
-    return fishes.iter().sum();
+fn part2(input: &[u64]) -> u64 {
+    fish_after(input, 256)
 }
 
 #[cfg(test)]
@@ -55,16 +64,32 @@ mod tests {
 
     #[test]
     fn test_parse_input() {
-        assert_eq!(parse_input(INPUT), vec![3, 4, 3, 1, 2]);
+        assert_eq!(parse_input(INPUT).unwrap(), vec![3, 4, 3, 1, 2]);
+    }
+
+    #[test]
+    fn rejects_bad_timers() {
+        assert_eq!(
+            parse_input("3,4,-3"),
+            Err(ParseError::new(1, 5, "expected u64, found \"-3\""))
+        );
+        assert_eq!(
+            parse_input("3,9,3"),
+            Err(ParseError::new(
+                1,
+                3,
+                "expected a timer from 0 to 8, found 9"
+            ))
+        );
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(INPUT)), 5934);
+        assert_eq!(part1(&parse_input(INPUT).unwrap()), 5934);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(INPUT)), 26984457539);
+        assert_eq!(part2(&parse_input(INPUT).unwrap()), 26984457539);
     }
 }
@@ -1,16 +1,14 @@
+use aoc2021::parse::{parse_separated, ParseError};
 use std::collections::HashMap;
 
 #[aoc2021::main(07)]
-fn main(input: &str) -> (i32, i32) {
-    let parsed_input = parse_input(input);
-    (part1(&parsed_input), part2(&parsed_input))
+fn main(input: &str) -> Result<(i32, i32), ParseError> {
+    let parsed_input = parse_input(input)?;
+    Ok((part1(&parsed_input), part2(&parsed_input)))
 }
 
-fn parse_input(input: &str) -> Vec<i32> {
-    input
-        .split(",")
-        .map(|s| s.parse::<i32>().unwrap())
-        .collect()
+fn parse_input(input: &str) -> Result<Vec<i32>, ParseError> {
+    parse_separated(input, ",")
 }
 
 fn part1(input: &Vec<i32>) -> i32 {
@@ -73,16 +71,27 @@ mod tests {
 
     #[test]
     fn test_parse_input() {
-        assert_eq!(parse_input(INPUT), vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
+        assert_eq!(
+            parse_input(INPUT).unwrap(),
+            vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_positions() {
+        assert_eq!(
+            parse_input("16,1;2,0"),
+            Err(ParseError::new(1, 4, "expected i32, found \"1;2\""))
+        );
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(INPUT)), 37);
+        assert_eq!(part1(&parse_input(INPUT).unwrap()), 37);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(INPUT)), 168);
+        assert_eq!(part2(&parse_input(INPUT).unwrap()), 168);
     }
 }
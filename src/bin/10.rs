@@ -1,13 +1,24 @@
+use aoc2021::parse::{parse_lines, ParseError};
 use itertools::Itertools;
 
 #[aoc2021::main(10)]
-fn main(input: &str) -> (u64, u64) {
-    let parsed_input = parse_input(input);
-    (part1(&parsed_input), part2(&parsed_input))
+fn main(input: &str) -> Result<(u64, u64), ParseError> {
+    let parsed_input = parse_input(input)?;
+    Ok((part1(&parsed_input), part2(&parsed_input)))
 }
 
-fn parse_input(input: &str) -> Vec<&str> {
-    input.lines().collect_vec()
+fn parse_input(input: &str) -> Result<Vec<&str>, ParseError> {
+    parse_lines(input, |line| match line.find(|c| !"()[]{}<>".contains(c)) {
+        Some(i) => Err(ParseError::at(
+            line,
+            &line[i..],
+            format!(
+                "expected one of ()[]{{}}<>, found {:?}",
+                line[i..].chars().next().unwrap()
+            ),
+        )),
+        None => Ok(line),
+    })
 }
 
 fn part_1_points_for_char(c: char) -> u64 {
@@ -84,9 +95,7 @@ fn completion_string(input: &str) -> Option<String> {
         }
     }
 
-    Some(String::from_iter(
-        stack.into_iter().rev().map(|c| closing_char(c)),
-    ))
+    Some(String::from_iter(stack.into_iter().rev().map(closing_char)))
 }
 
 fn part_2_score(string: &str) -> u64 {
@@ -196,11 +205,23 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(INPUT)), 26397);
+        assert_eq!(part1(&parse_input(INPUT).unwrap()), 26397);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(INPUT)), 288957);
+        assert_eq!(part2(&parse_input(INPUT).unwrap()), 288957);
+    }
+
+    #[test]
+    fn rejects_unknown_characters() {
+        assert_eq!(
+            parse_input("[({(<(())[]>[[{[]{<()<>>\n[(()[<>])]x({[<{<<[]>>("),
+            Err(ParseError::new(
+                2,
+                11,
+                "expected one of ()[]{}<>, found 'x'"
+            ))
+        );
     }
 }
@@ -1,98 +1,82 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use aoc2021::{graph::Graph, parse::ParseError};
 
 fn is_small(string: &str) -> bool {
     string.chars().all(|c| c.is_ascii_lowercase())
 }
 
 #[aoc2021::main(12)]
-fn main(input: &str) -> (u32, u32) {
-    let parsed_input = parse_input(input);
-    (part1(&parsed_input), part2(&parsed_input))
+fn main(input: &str) -> Result<(u32, u32), ParseError> {
+    let parsed_input = parse_input(input)?;
+    Ok((part1(&parsed_input), part2(&parsed_input)))
 }
 
-fn parse_input(input: &str) -> HashMap<&str, HashSet<&str>> {
-    let mut caves = HashMap::new();
-    for line in input.lines() {
-        let (start, end) = line.split_once("-").unwrap();
-        caves.entry(start).or_insert(HashSet::new()).insert(end);
-        caves.entry(end).or_insert(HashSet::new()).insert(start);
-    }
-    caves
+fn parse_input(input: &str) -> Result<Graph<&str>, ParseError> {
+    Graph::from_edges(input, "cave")
 }
 
-fn part1(input: &HashMap<&str, HashSet<&str>>) -> u32 {
-    let mut closed_paths = VecDeque::<Vec<&str>>::new();
-    let mut open_paths = VecDeque::<Vec<&str>>::new();
-    open_paths.push_back(vec!["start"]);
-
-    while !open_paths.is_empty() {
-        let mut current_path = open_paths.pop_front().unwrap();
-        let current_cave = current_path.last().unwrap();
-        let possible_next_caves = &input[current_cave];
-        for next_cave in possible_next_caves {
-            if next_cave == &"end" {
-                current_path.push(next_cave);
-                closed_paths.push_back(current_path.clone());
-                continue;
-            }
-
-            if is_small(next_cave) && current_path.contains(&next_cave) {
-                continue;
-            }
-
-            let mut new_path = current_path.clone();
-            new_path.push(next_cave);
-            open_paths.push_back(new_path);
+/// Counts the paths from `cave` to the end cave that never return to the
+/// start cave. `visits` holds how many times each small cave is already on
+/// the path, and `revisit_allowed` whether one of them may be visited again.
+fn count_paths(
+    caves: &Graph<&str>,
+    cave: usize,
+    small: &[bool],
+    visits: &mut [u8],
+    revisit_allowed: bool,
+) -> u32 {
+    if *caves.name(cave) == "end" {
+        return 1;
+    }
+
+    let mut paths = 0;
+    for &next in caves.neighbors(cave) {
+        if *caves.name(next) == "start" {
+            continue;
         }
+        let revisit = small[next] && visits[next] > 0;
+        if revisit && !revisit_allowed {
+            continue;
+        }
+
+        visits[next] += 1;
+        paths += count_paths(caves, next, small, visits, revisit_allowed && !revisit);
+        visits[next] -= 1;
     }
+    paths
+}
 
-    closed_paths.len() as u32
+fn paths_from_start(caves: &Graph<&str>, revisit_allowed: bool) -> u32 {
+    let Some(start) = caves.id("start") else {
+        return 0;
+    };
+    let small = (0..caves.len())
+        .map(|id| is_small(caves.name(id)))
+        .collect::<Vec<_>>();
+    let mut visits = vec![0; caves.len()];
+    count_paths(caves, start, &small, &mut visits, revisit_allowed)
 }
 
-fn part2(input: &HashMap<&str, HashSet<&str>>) -> u32 {
-    let mut closed_paths = VecDeque::<Vec<&str>>::new();
-    // Vector of open paths and a map of the lowercase cave visit count
-    let mut open_paths = VecDeque::<(Vec<&str>, HashMap<&str, usize>)>::new();
-    open_paths.push_back((vec!["start"], HashMap::new()));
-
-    while !open_paths.is_empty() {
-        let (current_path, lower_case_cave_counts) = open_paths.pop_front().unwrap();
-        let current_cave = current_path.last().unwrap();
-        let possible_next_caves = &input[current_cave];
-        for next_cave in possible_next_caves {
-            if next_cave == &"start" {
-                continue;
-            }
-
-            if next_cave == &"end" {
-                let mut new_path = current_path.clone();
-                new_path.push(&next_cave);
-                closed_paths.push_back(new_path);
-                continue;
-            }
-
-            let visit_count = lower_case_cave_counts.get(next_cave).unwrap_or(&0);
-            let any_visited_more_than_twice = lower_case_cave_counts.values().any(|v| v >= &2);
-            if is_small(next_cave) && visit_count > &0 && any_visited_more_than_twice {
-                continue;
-            }
-
-            let mut new_path = current_path.clone();
-            let mut new_lower_case_letters = lower_case_cave_counts.clone();
-            if is_small(&next_cave) {
-                new_lower_case_letters.insert(&next_cave, visit_count + 1);
-            }
-            new_path.push(next_cave);
-            open_paths.push_back((new_path, new_lower_case_letters));
-        }
-    }
+fn part1(input: &Graph<&str>) -> u32 {
+    paths_from_start(input, false)
+}
 
-    closed_paths.len() as u32
+fn part2(input: &Graph<&str>) -> u32 {
+    paths_from_start(input, true)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
+
+    fn neighbors<'a>(caves: &Graph<&'a str>, cave: &str) -> HashSet<&'a str> {
+        let id = caves.id(cave).unwrap();
+        caves
+            .neighbors(id)
+            .iter()
+            .map(|&n| *caves.name(n))
+            .collect()
+    }
 
     const INPUT1: &str = "start-A
 start-b
@@ -134,19 +118,37 @@ start-RW";
 
     #[test]
     fn parse_input_test() {
-        let caves = parse_input(INPUT1);
-        assert_eq!(caves["start"], vec!["A", "b"].into_iter().collect());
+        let caves = parse_input(INPUT1).unwrap();
         assert_eq!(
-            caves["A"],
+            neighbors(&caves, "start"),
+            vec!["A", "b"].into_iter().collect()
+        );
+        assert_eq!(
+            neighbors(&caves, "A"),
             vec!["start", "c", "b", "end"].into_iter().collect()
         );
         assert_eq!(
-            caves["b"],
+            neighbors(&caves, "b"),
             vec!["start", "A", "d", "end"].into_iter().collect()
         );
-        assert_eq!(caves["c"], vec!["A"].into_iter().collect());
-        assert_eq!(caves["d"], vec!["b"].into_iter().collect());
-        assert_eq!(caves["end"], vec!["A", "b"].into_iter().collect());
+        assert_eq!(neighbors(&caves, "c"), vec!["A"].into_iter().collect());
+        assert_eq!(neighbors(&caves, "d"), vec!["b"].into_iter().collect());
+        assert_eq!(
+            neighbors(&caves, "end"),
+            vec!["A", "b"].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn parse_input_rejects_malformed_edges() {
+        assert_eq!(
+            parse_input("start-A\nA~b"),
+            Err(ParseError::new(2, 1, "expected \"-\" after here"))
+        );
+        assert_eq!(
+            parse_input("start-A\nA-"),
+            Err(ParseError::new(2, 3, "expected a cave name"))
+        );
     }
 
     #[test]
@@ -157,31 +159,31 @@ start-RW";
 
     #[test]
     fn test_part1_input1() {
-        assert_eq!(part1(&parse_input(INPUT1)), 10);
+        assert_eq!(part1(&parse_input(INPUT1).unwrap()), 10);
     }
 
     #[test]
     fn test_part1_input2() {
-        assert_eq!(part1(&parse_input(INPUT2)), 19);
+        assert_eq!(part1(&parse_input(INPUT2).unwrap()), 19);
     }
 
     #[test]
     fn test_part1_input3() {
-        assert_eq!(part1(&parse_input(INPUT3)), 226);
+        assert_eq!(part1(&parse_input(INPUT3).unwrap()), 226);
     }
 
     #[test]
     fn test_part2_input1() {
-        assert_eq!(part2(&parse_input(INPUT1)), 36);
+        assert_eq!(part2(&parse_input(INPUT1).unwrap()), 36);
     }
 
     #[test]
     fn test_part2_input2() {
-        assert_eq!(part2(&parse_input(INPUT2)), 103);
+        assert_eq!(part2(&parse_input(INPUT2).unwrap()), 103);
     }
 
     #[test]
     fn test_part2_input3() {
-        assert_eq!(part2(&parse_input(INPUT3)), 3509);
+        assert_eq!(part2(&parse_input(INPUT3).unwrap()), 3509);
     }
 }
@@ -0,0 +1,320 @@
+use crate::parse::ParseError;
+use std::{fmt, ops::Not, str::FromStr};
+
+const WORD: usize = u64::BITS as usize;
+
+/// A string of bits of any length, such as a line of day 03's diagnostic
+/// report. Bits are indexed from the most significant (leftmost) end.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bits {
+    // Packed most significant first, so that comparing words compares values.
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bits {
+    /// `len` zero bits.
+    pub fn zeros(len: usize) -> Bits {
+        Bits {
+            words: vec![0; len.div_ceil(WORD)],
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The `i`th bit from the left.
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "bit {} out of range for {} bits", i, self.len);
+        self.words[i / WORD] >> (WORD - 1 - i % WORD) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        assert!(i < self.len, "bit {} out of range for {} bits", i, self.len);
+        let mask = 1 << (WORD - 1 - i % WORD);
+        if value {
+            self.words[i / WORD] |= mask;
+        } else {
+            self.words[i / WORD] &= !mask;
+        }
+    }
+
+    /// The bits from left to right.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// The value of these bits as an unsigned integer, or `None` if it does
+    /// not fit in a `T`.
+    pub fn to_int<T: TryFrom<u128>>(&self) -> Option<T> {
+        let high = self.len.saturating_sub(u128::BITS as usize);
+        if (0..high).any(|i| self.get(i)) {
+            return None;
+        }
+        let value = (high..self.len).fold(0u128, |acc, i| acc << 1 | self.get(i) as u128);
+        T::try_from(value).ok()
+    }
+
+    /// How many of `rows` have a one in each column. All rows must be the
+    /// same length.
+    pub fn column_counts(rows: &[Bits]) -> Vec<usize> {
+        let len = rows.first().map_or(0, Bits::len);
+        let mut counts = vec![0; len];
+        for row in rows {
+            assert_eq!(row.len, len, "rows of differing lengths");
+            for (i, count) in counts.iter_mut().enumerate() {
+                *count += row.get(i) as usize;
+            }
+        }
+        counts
+    }
+}
+
+impl FromIterator<bool> for Bits {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Bits {
+        let bools = iter.into_iter().collect::<Vec<_>>();
+        let mut bits = Bits::zeros(bools.len());
+        for (i, bit) in bools.into_iter().enumerate() {
+            bits.set(i, bit);
+        }
+        bits
+    }
+}
+
+impl FromStr for Bits {
+    type Err = ParseError;
+
+    /// Parses a string of `0`s and `1`s, e.g. `10110`.
+    fn from_str(s: &str) -> Result<Bits, ParseError> {
+        s.chars()
+            .enumerate()
+            .map(|(i, c)| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(ParseError::new(
+                    1,
+                    i + 1,
+                    format!("expected 0 or 1, found {:?}", c),
+                )),
+            })
+            .collect()
+    }
+}
+
+impl Not for &Bits {
+    type Output = Bits;
+
+    fn not(self) -> Bits {
+        self.iter().map(|bit| !bit).collect()
+    }
+}
+
+impl fmt::Display for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for bit in self.iter() {
+            write!(f, "{}", bit as u8)?;
+        }
+        Ok(())
+    }
+}
+
+/// A binary trie of equal-length [`Bits`] that counts the values below each
+/// node, so that a value can be picked out one bit at a time by how many
+/// others share each prefix.
+#[derive(Debug, Clone)]
+pub struct BitTrie {
+    nodes: Vec<TrieNode>,
+    width: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TrieNode {
+    children: [Option<usize>; 2],
+    count: usize,
+}
+
+impl BitTrie {
+    /// An empty trie of values `width` bits long.
+    pub fn new(width: usize) -> BitTrie {
+        BitTrie {
+            nodes: vec![TrieNode::default()],
+            width,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of values inserted, counting repeats.
+    pub fn len(&self) -> usize {
+        self.nodes[0].count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn insert(&mut self, bits: &Bits) {
+        assert_eq!(bits.len(), self.width, "bits of the wrong width");
+        let mut node = 0;
+        self.nodes[node].count += 1;
+        for bit in bits.iter() {
+            let child = match self.nodes[node].children[bit as usize] {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children[bit as usize] = Some(child);
+                    child
+                }
+            };
+            node = child;
+            self.nodes[node].count += 1;
+        }
+    }
+
+    fn count(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |node| self.nodes[node].count)
+    }
+
+    /// Finds a value by descending from the most significant bit, calling
+    /// `choose(i, zeros, ones)` with the number of remaining values that have
+    /// each bit at index `i` to pick which of them to keep. A choice that no
+    /// value has is overridden, so this only returns `None` if the trie is
+    /// empty. Takes `O(width)` time.
+    pub fn search(&self, mut choose: impl FnMut(usize, usize, usize) -> bool) -> Option<Bits> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut node = 0;
+        let mut bits = Bits::zeros(self.width);
+        for i in 0..self.width {
+            let [zero, one] = self.nodes[node].children;
+            let (zeros, ones) = (self.count(zero), self.count(one));
+            let bit = match (zeros, ones) {
+                (_, 0) => false,
+                (0, _) => true,
+                _ => choose(i, zeros, ones),
+            };
+            bits.set(i, bit);
+            node = if bit { one } else { zero }.unwrap_or_else(|| unreachable!("counted"));
+        }
+        Some(bits)
+    }
+}
+
+impl<'a> FromIterator<&'a Bits> for BitTrie {
+    /// Builds a trie of values as wide as the first one.
+    fn from_iter<I: IntoIterator<Item = &'a Bits>>(iter: I) -> BitTrie {
+        let mut iter = iter.into_iter().peekable();
+        let mut trie = BitTrie::new(iter.peek().map_or(0, |bits| bits.len()));
+        for bits in iter {
+            trie.insert(bits);
+        }
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_displays() {
+        let bits = "10110".parse::<Bits>().unwrap();
+        assert_eq!(bits.len(), 5);
+        assert_eq!(bits.to_string(), "10110");
+        assert_eq!(
+            "10210".parse::<Bits>(),
+            Err(ParseError::new(1, 3, "expected 0 or 1, found '2'"))
+        );
+        assert!("".parse::<Bits>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn accesses_bits_from_the_left() {
+        let mut bits = "10110".parse::<Bits>().unwrap();
+        assert_eq!(
+            bits.iter().collect::<Vec<_>>(),
+            [true, false, true, true, false]
+        );
+        bits.set(1, true);
+        bits.set(0, false);
+        assert_eq!(bits.to_string(), "01110");
+        assert_eq!(bits.count_ones(), 3);
+        assert_eq!((!&bits).to_string(), "10001");
+    }
+
+    #[test]
+    fn converts_to_integers() {
+        let bits = "10110".parse::<Bits>().unwrap();
+        assert_eq!(bits.to_int::<u8>(), Some(22));
+        assert_eq!(bits.to_int::<u64>(), Some(22));
+
+        let wide = format!("1{}", "0".repeat(40)).parse::<Bits>().unwrap();
+        assert_eq!(wide.to_int::<u64>(), Some(1 << 40));
+        assert_eq!(wide.to_int::<u32>(), None);
+
+        let padded = format!("{}101", "0".repeat(200)).parse::<Bits>().unwrap();
+        assert_eq!(padded.to_int::<u16>(), Some(5));
+        let huge = format!("1{}", "0".repeat(128)).parse::<Bits>().unwrap();
+        assert_eq!(huge.to_int::<u128>(), None);
+    }
+
+    #[test]
+    fn spans_multiple_words() {
+        let s = "1".repeat(70) + "01";
+        let bits = s.parse::<Bits>().unwrap();
+        assert_eq!(bits.to_string(), s);
+        assert_eq!(bits.count_ones(), 71);
+        assert!(!bits.get(70));
+        assert!(bits.get(71));
+        assert_eq!((!&bits).count_ones(), 1);
+    }
+
+    #[test]
+    fn counts_columns() {
+        let rows = ["110", "011", "010"]
+            .map(|s| s.parse::<Bits>().unwrap())
+            .to_vec();
+        assert_eq!(Bits::column_counts(&rows), vec![1, 3, 1]);
+        assert_eq!(Bits::column_counts(&[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn orders_by_value() {
+        let a = "0110".parse::<Bits>().unwrap();
+        let b = "1001".parse::<Bits>().unwrap();
+        assert!(a < b);
+    }
+
+    #[test]
+    fn searches_tries() {
+        let rows = ["110", "011", "010", "111"].map(|s| s.parse::<Bits>().unwrap());
+        let trie = rows.iter().collect::<BitTrie>();
+        assert_eq!(trie.len(), 4);
+        assert_eq!(trie.width(), 3);
+
+        let largest = trie.search(|_, _, _| true).unwrap();
+        assert_eq!(largest.to_string(), "111");
+        let mut counts = vec![];
+        let smallest = trie.search(|i, zeros, ones| {
+            counts.push((i, zeros, ones));
+            false
+        });
+        assert_eq!(smallest.unwrap().to_string(), "010");
+        assert_eq!(counts, [(0, 2, 2), (2, 1, 1)]);
+        assert_eq!(BitTrie::new(3).search(|_, _, _| true), None);
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    borrow::Borrow,
+    cmp::Reverse,
+    collections::HashMap,
+    hash::Hash,
+    ops::{AddAssign, Index},
+};
+
+/// A multiset: how many times each key has been seen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counter<T: Hash + Eq> {
+    counts: HashMap<T, usize>,
+}
+
+impl<T: Hash + Eq> Default for Counter<T> {
+    fn default() -> Self {
+        Counter {
+            counts: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq> Counter<T> {
+    pub fn new() -> Counter<T> {
+        Counter::default()
+    }
+
+    pub fn add(&mut self, key: T) {
+        self.add_n(key, 1);
+    }
+
+    pub fn add_n(&mut self, key: T, n: usize) {
+        *self.counts.entry(key).or_insert(0) += n;
+    }
+
+    /// How many times `key` has been seen, which is 0 for unseen keys.
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> usize
+    where
+        T: Borrow<Q>,
+    {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    /// The number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The number of keys seen, counting repeats.
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Adds every count of `other` to this counter.
+    pub fn merge(&mut self, other: Counter<T>) {
+        for (key, n) in other.counts {
+            self.add_n(key, n);
+        }
+    }
+
+    /// Each distinct key with its count, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.counts.iter().map(|(key, &n)| (key, n))
+    }
+
+    /// The number of keys seen more than `n` times.
+    pub fn count_more_than(&self, n: usize) -> usize {
+        self.counts.values().filter(|&&count| count > n).count()
+    }
+
+    /// The key seen most often, preferring the smallest key on a tie.
+    pub fn most_common(&self) -> Option<(&T, usize)>
+    where
+        T: Ord,
+    {
+        self.iter().max_by_key(|&(key, n)| (n, Reverse(key)))
+    }
+
+    /// The key seen least often, preferring the smallest key on a tie.
+    pub fn least_common(&self) -> Option<(&T, usize)>
+    where
+        T: Ord,
+    {
+        self.iter().min_by_key(|&(key, n)| (n, key))
+    }
+}
+
+impl<T: Hash + Eq> FromIterator<T> for Counter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Counter<T> {
+        let mut counter = Counter::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<T: Hash + Eq> Extend<T> for Counter<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for key in iter {
+            self.add(key);
+        }
+    }
+}
+
+/// A [`Counter`] of the keys `0..N`, stored in an array, such as day 06's
+/// fish timers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DenseCounter<const N: usize> {
+    counts: [usize; N],
+}
+
+impl<const N: usize> Default for DenseCounter<N> {
+    fn default() -> Self {
+        DenseCounter { counts: [0; N] }
+    }
+}
+
+impl<const N: usize> DenseCounter<N> {
+    pub fn new() -> DenseCounter<N> {
+        DenseCounter::default()
+    }
+
+    pub fn add(&mut self, key: usize) {
+        self.add_n(key, 1);
+    }
+
+    pub fn add_n(&mut self, key: usize, n: usize) {
+        self.counts[key] += n;
+    }
+
+    pub fn get(&self, key: usize) -> usize {
+        self.counts[key]
+    }
+
+    /// The number of keys seen, counting repeats.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    pub fn merge(&mut self, other: &DenseCounter<N>) {
+        for (count, n) in self.counts.iter_mut().zip(other.counts) {
+            *count += n;
+        }
+    }
+
+    /// Every key in `0..N` with its count, including unseen keys.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.counts.iter().copied().enumerate()
+    }
+
+    /// The number of keys seen more than `n` times.
+    pub fn count_more_than(&self, n: usize) -> usize {
+        self.counts.iter().filter(|&&count| count > n).count()
+    }
+
+    /// The key seen most often, preferring the smallest key on a tie.
+    pub fn most_common(&self) -> Option<(usize, usize)> {
+        self.iter().max_by_key(|&(key, n)| (n, Reverse(key)))
+    }
+
+    /// The key seen least often, including keys never seen, preferring the
+    /// smallest key on a tie.
+    pub fn least_common(&self) -> Option<(usize, usize)> {
+        self.iter().min_by_key(|&(key, n)| (n, key))
+    }
+
+    /// Moves the count of every key to the key `n` below it, with the lowest
+    /// keys wrapping around to the top.
+    pub fn rotate_left(&mut self, n: usize) {
+        self.counts.rotate_left(n);
+    }
+
+    pub fn as_array(&self) -> &[usize; N] {
+        &self.counts
+    }
+}
+
+impl<const N: usize> Index<usize> for DenseCounter<N> {
+    type Output = usize;
+
+    fn index(&self, key: usize) -> &usize {
+        &self.counts[key]
+    }
+}
+
+impl<const N: usize> AddAssign<&DenseCounter<N>> for DenseCounter<N> {
+    fn add_assign(&mut self, other: &DenseCounter<N>) {
+        self.merge(other);
+    }
+}
+
+impl<const N: usize> FromIterator<usize> for DenseCounter<N> {
+    /// Counts the keys of `iter`, all of which must be less than `N`.
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> DenseCounter<N> {
+        let mut counter = DenseCounter::new();
+        for key in iter {
+            counter.add(key);
+        }
+        counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_keys() {
+        let mut counter = "abracadabra".chars().collect::<Counter<_>>();
+        assert_eq!(counter.get(&'a'), 5);
+        assert_eq!(counter.get(&'z'), 0);
+        assert_eq!(counter.len(), 5);
+        assert_eq!(counter.total(), 11);
+        assert_eq!(counter.count_more_than(1), 3);
+        assert_eq!(counter.most_common(), Some((&'a', 5)));
+        assert_eq!(counter.least_common(), Some((&'c', 1)));
+
+        counter.merge("zzzzzzz".chars().collect());
+        assert_eq!(counter.most_common(), Some((&'z', 7)));
+        assert_eq!(Counter::<char>::new().most_common(), None);
+    }
+
+    #[test]
+    fn looks_up_borrowed_keys() {
+        let counter = ["start", "end", "start"]
+            .map(String::from)
+            .into_iter()
+            .collect::<Counter<_>>();
+        assert_eq!(counter.get("start"), 2);
+    }
+
+    #[test]
+    fn counts_dense_keys() {
+        let mut counter = [3, 4, 3, 1, 2].into_iter().collect::<DenseCounter<9>>();
+        assert_eq!(counter.as_array(), &[0, 1, 1, 2, 1, 0, 0, 0, 0]);
+        assert_eq!(counter[3], 2);
+        assert_eq!(counter.total(), 5);
+        assert_eq!(counter.count_more_than(0), 4);
+        assert_eq!(counter.most_common(), Some((3, 2)));
+        assert_eq!(counter.least_common(), Some((0, 0)));
+
+        counter.rotate_left(1);
+        assert_eq!(counter.as_array(), &[1, 1, 2, 1, 0, 0, 0, 0, 0]);
+        counter += &[8].into_iter().collect();
+        assert_eq!(counter.get(8), 1);
+    }
+}
@@ -0,0 +1,428 @@
+//! Graphs with interned nodes, plus searches over implicit state spaces where
+//! the neighbours of a state are produced on demand by a closure.
+
+use crate::parse::{parse_lines, scan_fields, ParseError};
+use std::{
+    borrow::Borrow,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// A graph whose nodes are stored once and referred to by `usize` ids, in
+/// the order they were first seen.
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    names: Vec<N>,
+    ids: HashMap<N, usize>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl<N: Eq + Hash> PartialEq for Graph<N> {
+    fn eq(&self, other: &Graph<N>) -> bool {
+        // The ids are derived from the names, so they need not be compared.
+        self.names == other.names && self.edges == other.edges
+    }
+}
+
+impl<N: Eq + Hash> Eq for Graph<N> {}
+
+impl<N> Default for Graph<N> {
+    fn default() -> Graph<N> {
+        Graph {
+            names: vec![],
+            ids: HashMap::new(),
+            edges: vec![],
+        }
+    }
+}
+
+impl<N: Clone + Eq + Hash> Graph<N> {
+    pub fn new() -> Graph<N> {
+        Graph::default()
+    }
+
+    /// The id of `node`, adding it to the graph if it is new.
+    pub fn intern(&mut self, node: N) -> usize {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(node.clone());
+        self.ids.insert(node, id);
+        self.edges.push(vec![]);
+        id
+    }
+
+    pub fn id<Q>(&self, node: &Q) -> Option<usize>
+    where
+        N: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.ids.get(node).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &N {
+        &self.names[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Adds an edge from `from` to `to` only, returning both ids.
+    pub fn add_directed_edge(&mut self, from: N, to: N) -> (usize, usize) {
+        let (from, to) = (self.intern(from), self.intern(to));
+        if !self.edges[from].contains(&to) {
+            self.edges[from].push(to);
+        }
+        (from, to)
+    }
+
+    /// Adds an edge in both directions, returning both ids.
+    pub fn add_edge(&mut self, a: N, b: N) -> (usize, usize) {
+        let (a, b) = self.add_directed_edge(a, b);
+        if !self.edges[b].contains(&a) {
+            self.edges[b].push(a);
+        }
+        (a, b)
+    }
+
+    pub fn neighbors(&self, id: usize) -> &[usize] {
+        &self.edges[id]
+    }
+
+    /// The fewest-edges path between two nodes, as ids from `from` to `to`.
+    pub fn shortest_path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        bfs(from, |&id| self.neighbors(id).to_vec(), |&id| id == to)
+    }
+}
+
+impl<'a> Graph<&'a str> {
+    /// Builds an undirected graph from lines of `a-b` edges, as in day 12,
+    /// calling the nodes `noun`s in errors.
+    pub fn from_edges(input: &'a str, noun: &str) -> Result<Graph<&'a str>, ParseError> {
+        let mut graph = Graph::new();
+        for (a, b) in parse_lines(input, |line| parse_edge(line, noun))? {
+            graph.add_edge(a, b);
+        }
+        Ok(graph)
+    }
+}
+
+fn parse_edge<'a>(line: &'a str, noun: &str) -> Result<(&'a str, &'a str), ParseError> {
+    let fields = scan_fields(line, "{}-{}")?;
+    if let Some(empty) = fields.iter().find(|f| f.is_empty()) {
+        return Err(ParseError::at(
+            line,
+            empty,
+            format!("expected a {} name", noun),
+        ));
+    }
+    Ok((fields[0], fields[1]))
+}
+
+/// Follows `parents` back from `end` to the state without a parent.
+pub fn reconstruct_path<S: Clone + Eq + Hash>(parents: &HashMap<S, S>, end: S) -> Vec<S> {
+    let mut path = vec![end];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Breadth-first search for the path with the fewest steps from `start` to
+/// a state satisfying `is_goal`.
+pub fn bfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Vec<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut parents = HashMap::new();
+    let mut seen = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        if is_goal(&state) {
+            return Some(reconstruct_path(&parents, state));
+        }
+        for next in successors(&state) {
+            if seen.insert(next.clone()) {
+                parents.insert(next.clone(), state.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// The number of steps from `start` to every state reachable from it.
+pub fn bfs_distances<S, I>(start: S, mut successors: impl FnMut(&S) -> I) -> HashMap<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        let distance = distances[&state] + 1;
+        for next in successors(&state) {
+            distances.entry(next.clone()).or_insert_with(|| {
+                queue.push_back(next);
+                distance
+            });
+        }
+    }
+    distances
+}
+
+/// Depth-first search for any path from `start` to a state satisfying
+/// `is_goal`.
+pub fn dfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Vec<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut parents = HashMap::new();
+    let mut seen = HashSet::from([start.clone()]);
+    let mut stack = vec![start];
+    while let Some(state) = stack.pop() {
+        if is_goal(&state) {
+            return Some(reconstruct_path(&parents, state));
+        }
+        for next in successors(&state) {
+            if seen.insert(next.clone()) {
+                parents.insert(next.clone(), state.clone());
+                stack.push(next);
+            }
+        }
+    }
+    None
+}
+
+/// Every state reachable from `start`, in depth-first order.
+pub fn dfs_reachable<S, I>(start: S, mut successors: impl FnMut(&S) -> I) -> Vec<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut seen = HashSet::new();
+    let mut order = vec![];
+    let mut stack = vec![start];
+    while let Some(state) = stack.pop() {
+        if !seen.insert(state.clone()) {
+            continue;
+        }
+        let next = successors(&state).into_iter().collect::<Vec<_>>();
+        stack.extend(next.into_iter().rev());
+        order.push(state);
+    }
+    order
+}
+
+/// Dijkstra's algorithm: the cheapest path from `start` to a state satisfying
+/// `is_goal`, where `successors` yields each next state with the cost of
+/// moving to it. Returns the path and its total cost.
+pub fn dijkstra<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<(Vec<S>, C)>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+/// A* search: like [`dijkstra`], but guided by `heuristic`, which must never
+/// overestimate the remaining cost to a goal.
+pub fn astar<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<(Vec<S>, C)>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    // States are interned so that the heap and parent links only hold ids.
+    let mut states = vec![start.clone()];
+    let mut ids = HashMap::from([(start.clone(), 0)]);
+    let mut parents: Vec<Option<usize>> = vec![None];
+    let mut costs = vec![C::default()];
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&start), C::default(), 0))]);
+
+    while let Some(Reverse((_, cost, id))) = heap.pop() {
+        // A cheaper way to this state was found after this entry was pushed.
+        if cost > costs[id] {
+            continue;
+        }
+        if is_goal(&states[id]) {
+            let mut path = vec![id];
+            while let Some(parent) = parents[*path.last().unwrap()] {
+                path.push(parent);
+            }
+            let path = path.into_iter().rev().map(|i| states[i].clone()).collect();
+            return Some((path, costs[id]));
+        }
+        let state = states[id].clone();
+        for (next, step) in successors(&state) {
+            let cost = costs[id] + step;
+            let next_id = match ids.get(&next) {
+                Some(&next_id) if costs[next_id] <= cost => continue,
+                Some(&next_id) => next_id,
+                None => {
+                    states.push(next.clone());
+                    ids.insert(next.clone(), states.len() - 1);
+                    parents.push(None);
+                    costs.push(cost);
+                    states.len() - 1
+                }
+            };
+            parents[next_id] = Some(id);
+            costs[next_id] = cost;
+            heap.push(Reverse((cost + heuristic(&next), cost, next_id)));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::Grid, point::Point};
+
+    const EDGES: &str = "start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end";
+
+    #[test]
+    fn interns_nodes() {
+        let mut graph = Graph::new();
+        assert_eq!(graph.intern("a"), 0);
+        assert_eq!(graph.intern("b"), 1);
+        assert_eq!(graph.intern("a"), 0);
+        assert_eq!(graph.id("b"), Some(1));
+        assert_eq!(graph.id("c"), None);
+        assert_eq!(*graph.name(1), "b");
+        assert_eq!(graph.len(), 2);
+    }
+
+    #[test]
+    fn builds_from_edge_lists() {
+        let graph = Graph::from_edges(EDGES, "node").unwrap();
+        let a = graph.id("A").unwrap();
+        let mut neighbors = graph
+            .neighbors(a)
+            .iter()
+            .map(|&id| *graph.name(id))
+            .collect::<Vec<_>>();
+        neighbors.sort();
+        assert_eq!(neighbors, vec!["b", "c", "end", "start"]);
+        assert_eq!(
+            Graph::from_edges("a-b\nc", "node").err(),
+            Some(ParseError::new(2, 1, "expected \"-\" after here"))
+        );
+        assert_eq!(
+            Graph::from_edges("a-b\n-c", "node"),
+            Err(ParseError::new(2, 1, "expected a node name"))
+        );
+    }
+
+    #[test]
+    fn finds_shortest_paths_in_graphs() {
+        let graph = Graph::from_edges(EDGES, "node").unwrap();
+        let path = graph
+            .shortest_path(graph.id("c").unwrap(), graph.id("d").unwrap())
+            .unwrap();
+        let names = path.iter().map(|&id| *graph.name(id)).collect::<Vec<_>>();
+        assert_eq!(names, vec!["c", "A", "b", "d"]);
+    }
+
+    #[test]
+    fn searches_implicit_spaces() {
+        // Reach 10 from 1 by doubling or adding one.
+        let successors = |&n: &u32| [n + 1, n * 2].into_iter().filter(|&m| m <= 10);
+        assert_eq!(bfs(1, successors, |&n| n == 10), Some(vec![1, 2, 4, 5, 10]));
+        assert_eq!(bfs(1, successors, |&n| n == 11), None);
+        assert_eq!(bfs_distances(1, successors)[&10], 4);
+
+        let path = dfs(1, successors, |&n| n == 10).unwrap();
+        assert_eq!((path[0], *path.last().unwrap()), (1, 10));
+        assert!(path
+            .windows(2)
+            .all(|w| w[1] == w[0] + 1 || w[1] == w[0] * 2));
+        assert_eq!(dfs_reachable(1, successors).len(), 10);
+        assert_eq!(dfs_reachable(1, successors)[..3], [1, 2, 3]);
+    }
+
+    #[test]
+    fn finds_cheapest_paths() {
+        // The day 15 example: the cheapest path's risk is 40.
+        let grid = Grid::parse_digits(
+            "1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581",
+        )
+        .unwrap();
+        let end = Point::new(grid.width() - 1, grid.height() - 1);
+        let successors =
+            |&p: &Point<usize>| grid.neighbors4(p).map(|n| (n, grid[n])).collect::<Vec<_>>();
+
+        let (path, cost) = dijkstra(Point::new(0, 0), successors, |&p| p == end).unwrap();
+        assert_eq!(cost, 40);
+        assert_eq!((path[0], *path.last().unwrap()), (Point::new(0, 0), end));
+        assert_eq!(path[1..].iter().map(|&p| grid[p]).sum::<u32>(), 40);
+
+        let heuristic = |&p: &Point<usize>| p.manhattan(end) as u32;
+        let (_, cost) = astar(Point::new(0, 0), successors, heuristic, |&p| p == end).unwrap();
+        assert_eq!(cost, 40);
+    }
+
+    #[test]
+    fn expands_each_state_once() {
+        // `a` is first reached for 5, then for 2 through `b`, leaving a stale
+        // heap entry for it that is popped before the goal is.
+        let mut expanded = vec![];
+        let successors = |&s: &char| {
+            expanded.push(s);
+            match s {
+                's' => vec![('a', 5), ('b', 1)],
+                'b' => vec![('a', 1)],
+                'a' => vec![('g', 10)],
+                _ => vec![],
+            }
+        };
+        let (path, cost) = dijkstra('s', successors, |&s| s == 'g').unwrap();
+        assert_eq!((path, cost), (vec!['s', 'b', 'a', 'g'], 12));
+        assert_eq!(expanded, ['s', 'b', 'a']);
+    }
+}
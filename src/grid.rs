@@ -0,0 +1,320 @@
+use crate::{
+    parse::ParseError,
+    point::{Point, Vector},
+};
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+/// A rectangular grid stored row by row in a single `Vec`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from cells in row-major order, or `None` if there are
+    /// not exactly `width * height` of them.
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Option<Grid<T>> {
+        (cells.len() == width * height).then_some(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Builds a grid from a list of rows, or `None` if they are ragged.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Grid<T>> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Grid::from_vec(width, height, rows.into_iter().flatten().collect())
+    }
+
+    /// Parses one row per line, converting each character with `f`. Fails on
+    /// any character `f` rejects and on rows of differing lengths.
+    pub fn parse(input: &str, f: impl Fn(char) -> Option<T>) -> Result<Grid<T>, ParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+        for (y, line) in input.lines().enumerate() {
+            height += 1;
+            let row = line.trim();
+            for (i, c) in row.char_indices() {
+                let cell = f(c).ok_or_else(|| {
+                    let token = &row[i..i + c.len_utf8()];
+                    ParseError::at(line, token, format!("unexpected character {:?}", c))
+                        .offset_lines(y)
+                })?;
+                cells.push(cell);
+            }
+            let len = row.chars().count();
+            match width {
+                None => width = Some(len),
+                Some(width) if width != len => {
+                    return Err(ParseError::at(
+                        line,
+                        row,
+                        format!("expected {} columns, found {}", width, len),
+                    )
+                    .offset_lines(y))
+                }
+                _ => {}
+            }
+        }
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Point<usize>) -> bool {
+        p.x < self.width && p.y < self.height
+    }
+
+    pub fn get(&self, p: Point<usize>) -> Option<&T> {
+        self.contains(p)
+            .then(|| &self.cells[p.y * self.width + p.x])
+    }
+
+    pub fn get_mut(&mut self, p: Point<usize>) -> Option<&mut T> {
+        if !self.contains(p) {
+            return None;
+        }
+        Some(&mut self.cells[p.y * self.width + p.x])
+    }
+
+    /// Looks up `p` as if the grid repeated forever in every direction, or
+    /// `None` if the grid has no cells to repeat.
+    pub fn get_wrapped(&self, p: Point<isize>) -> Option<&T> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        let x = p.x.rem_euclid(self.width as isize) as usize;
+        let y = p.y.rem_euclid(self.height as isize) as usize;
+        Some(&self.cells[y * self.width + x])
+    }
+
+    /// Every point in the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point<usize>> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point { x, y }))
+    }
+
+    /// Every point in the grid alongside its value, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point<usize>, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    fn neighbors(
+        &self,
+        p: Point<usize>,
+        offsets: &'static [Vector<isize>],
+    ) -> impl Iterator<Item = Point<usize>> + '_ {
+        offsets
+            .iter()
+            .filter_map(move |&delta| p.offset(delta))
+            .filter(|&n| self.contains(n))
+    }
+
+    /// The up to four points above, left of, right of and below `p`.
+    pub fn neighbors4(&self, p: Point<usize>) -> impl Iterator<Item = Point<usize>> + '_ {
+        self.neighbors(p, &Point::ORTHOGONAL)
+    }
+
+    /// The up to eight points surrounding `p`, including diagonals.
+    pub fn neighbors8(&self, p: Point<usize>) -> impl Iterator<Item = Point<usize>> + '_ {
+        self.neighbors(p, &Point::ADJACENT)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {} out of bounds", x);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+}
+
+impl Grid<u32> {
+    /// Parses rows of decimal digits such as `2199943210`.
+    pub fn parse_digits(input: &str) -> Result<Grid<u32>, ParseError> {
+        Grid::parse(input, |c| c.to_digit(10))
+    }
+}
+
+impl Grid<char> {
+    pub fn parse_chars(input: &str) -> Result<Grid<char>, ParseError> {
+        Grid::parse(input, Some)
+    }
+}
+
+impl<T> Index<Point<usize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point<usize>) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("{:?} out of bounds", p))
+    }
+}
+
+impl<T> IndexMut<Point<usize>> for Grid<T> {
+    fn index_mut(&mut self, p: Point<usize>) -> &mut T {
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{:?} out of bounds", p))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "123
+456";
+
+    #[test]
+    fn parses_digits() {
+        let grid = Grid::parse_digits(INPUT).unwrap();
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid[Point::new(2, 1)], 6);
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(
+            Grid::parse_digits("12\n3x"),
+            Err(ParseError::new(2, 2, "unexpected character 'x'"))
+        );
+        assert_eq!(
+            Grid::parse_digits("12\n345"),
+            Err(ParseError::new(2, 1, "expected 2 columns, found 3"))
+        );
+        assert_eq!(
+            Grid::parse_digits("  12\n  3x"),
+            Err(ParseError::new(2, 4, "unexpected character 'x'"))
+        );
+        assert_eq!(
+            Grid::parse_digits("  12\n\t345"),
+            Err(ParseError::new(2, 2, "expected 2 columns, found 3"))
+        );
+    }
+
+    #[test]
+    fn parses_chars() {
+        let grid = Grid::parse_chars("#.\n.#").unwrap();
+        assert_eq!(grid[Point::new(0, 0)], '#');
+        assert_eq!(grid[Point::new(1, 0)], '.');
+    }
+
+    #[test]
+    fn keeps_the_height_of_empty_rows() {
+        let grid = Grid::parse_chars("\n\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (0, 2));
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.get_wrapped(Point::new(1, 1)), None);
+    }
+
+    #[test]
+    fn mutates_cells() {
+        let mut grid = Grid::filled(2, 2, 0);
+        grid[Point::new(1, 0)] = 5;
+        *grid.get_mut(Point::new(0, 1)).unwrap() += 2;
+        assert_eq!(grid.get_mut(Point::new(2, 0)), None);
+        assert_eq!(grid.to_string(), "05\n20");
+    }
+
+    #[test]
+    fn finds_neighbors() {
+        let grid = Grid::parse_digits(INPUT).unwrap();
+        let corner = grid.neighbors4(Point::new(0, 0)).collect::<Vec<_>>();
+        assert_eq!(corner, vec![Point::new(1, 0), Point::new(0, 1)]);
+        assert_eq!(grid.neighbors4(Point::new(1, 1)).count(), 3);
+        assert_eq!(grid.neighbors8(Point::new(1, 1)).count(), 5);
+        assert_eq!(grid.neighbors8(Point::new(1, 0)).count(), 5);
+    }
+
+    #[test]
+    fn iterates_rows_and_columns() {
+        let grid = Grid::parse_digits(INPUT).unwrap();
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(
+            grid.rows().collect::<Vec<_>>(),
+            vec![&[1, 2, 3], &[4, 5, 6]]
+        );
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        let columns = grid
+            .columns()
+            .map(|c| c.copied().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(columns, vec![vec![1, 4], vec![2, 5], vec![3, 6]]);
+    }
+
+    #[test]
+    fn wraps_around() {
+        let grid = Grid::parse_digits(INPUT).unwrap();
+        assert_eq!(grid.get_wrapped(Point::new(3, 0)), Some(&1));
+        assert_eq!(grid.get_wrapped(Point::new(-1, -1)), Some(&6));
+        assert_eq!(grid.get_wrapped(Point::new(7, 5)), Some(&5));
+        let empty = Grid::parse_digits("").unwrap();
+        assert_eq!(empty.get_wrapped(Point::new(0, 0)), None);
+    }
+
+    #[test]
+    fn iterates_points_with_values() {
+        let grid = Grid::from_rows(vec![vec!['a', 'b'], vec!['c', 'd']]).unwrap();
+        assert_eq!(
+            grid.iter().collect::<Vec<_>>(),
+            vec![
+                (Point::new(0, 0), &'a'),
+                (Point::new(1, 0), &'b'),
+                (Point::new(0, 1), &'c'),
+                (Point::new(1, 1), &'d'),
+            ]
+        );
+    }
+}
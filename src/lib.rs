@@ -1,32 +1,35 @@
-use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, AttributeArgs, Ident, ItemFn, Lit, NestedMeta};
+//! Support code shared by the daily solutions in `src/bin`.
+//!
+//! The `#[aoc2021::main(NN)]` attribute lives in the `aoc2021-macros` crate
+//! and is re-exported here, so days only ever depend on `aoc2021`.
 
-#[proc_macro_attribute]
-pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
-    let input_path = match &parse_macro_input!(args as AttributeArgs)[..] {
-        [NestedMeta::Lit(Lit::Int(day))] => format!("../../inputs/{}.txt", day.token()),
-        _ => panic!("Expected one integer argument"),
-    };
+pub use aoc2021_macros::main;
 
-    let mut aoc_solution = parse_macro_input!(input as ItemFn);
-    aoc_solution.sig.ident = Ident::new("aoc_solution", aoc_solution.sig.ident.span());
+use std::fmt::Display;
 
-    let tokens = quote! {
-      const INPUT: &str = include_str!(#input_path);
-      #aoc_solution
-      fn main() {
-        let now = ::std::time::Instant::now();
-        let (p1, p2) = aoc_solution(INPUT.trim_end());
-        let elapsed = now.elapsed();
-        println!("Part one: {}", p1);
-        println!("Part two: {}", p2);
-        if elapsed.as_millis() > 0 {
-          println!("Time: {}ms", elapsed.as_millis());
-        } else {
-          println!("Time: {}μs", elapsed.as_micros());
-        }
-      }
-    };
-    TokenStream::from(tokens)
+/// What a day's solution returns: either its two answers, or a `Result` of
+/// them for days whose input can be rejected.
+pub trait Answers {
+    fn into_answers(self) -> Result<(String, String), String>;
 }
+
+impl<A: Display, B: Display> Answers for (A, B) {
+    fn into_answers(self) -> Result<(String, String), String> {
+        Ok((self.0.to_string(), self.1.to_string()))
+    }
+}
+
+impl<A: Display, B: Display, E: Display> Answers for Result<(A, B), E> {
+    fn into_answers(self) -> Result<(String, String), String> {
+        self.map_err(|e| e.to_string())?.into_answers()
+    }
+}
+
+pub mod bits;
+pub mod counter;
+pub mod graph;
+pub mod grid;
+pub mod parse;
+pub mod point;
+pub mod raster;
+pub mod union_find;
@@ -1,30 +1,52 @@
-use itertools::Itertools;
-use std::{error::Error, fs, process::Command};
+mod runner;
 
-fn extract_microseconds(output: &str) -> Result<usize, Box<dyn Error>> {
-    let out = output.lines().last().unwrap();
-    let time = if out.ends_with("ms") {
-        out["Time: ".len()..out.len() - 2].parse::<usize>()? * 1000
-    } else {
-        out["Time: ".len()..out.len() - 3].parse::<usize>()?
-    };
-    Ok(time)
-}
+use runner::{
+    budget::load_budgets, compare::compare, dashboard::dashboard, days, parse_output, run_day,
+};
+use std::{env, error::Error, path::Path};
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let days = fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src/bin/"))?
-        .filter_map(|p| p.ok()?.path().file_stem()?.to_str().map(str::to_string))
-        .sorted()
-        .collect::<Vec<_>>();
+fn run_all(fail_over_budget: bool) -> Result<(), Box<dyn Error>> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let budgets = load_budgets(&root.join("budgets.txt"))?;
+    let days = days(root)?;
+    budgets.check_days(&days)?;
     let mut total_time = 0;
-    for day in &days {
-        let cmd = Command::new("cargo")
-            .args(["run", "--release", "--bin", day])
-            .output()?;
-        let output = String::from_utf8(cmd.stdout)?;
+    let mut over_budget = vec![];
+    for day in days {
+        let output = run_day(&day)?;
         println!("Day {}:\n{}", day, output);
-        total_time += extract_microseconds(&output)?;
+        let micros = parse_output(&output)?.micros;
+        if let Some(report) = budgets.day_report(&day, micros) {
+            println!("{}", report);
+        }
+        if budgets.day_over(&day, micros) {
+            over_budget.push(day);
+        }
+        total_time += micros;
     }
     println!("Total time: {}ms", total_time / 1000);
+    if let Some(report) = budgets.total_report(total_time) {
+        println!("{}", report);
+    }
+    if budgets.total_over(total_time) {
+        over_budget.push("total".to_string());
+    }
+
+    if fail_over_budget && !over_budget.is_empty() {
+        return Err(format!("over budget: {}", over_budget.join(", ")).into());
+    }
     Ok(())
 }
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => run_all(false),
+        ["--fail-over-budget"] => run_all(true),
+        ["compare", rev_a, rev_b] => compare(rev_a, rev_b),
+        ["dashboard"] => dashboard(),
+        _ => {
+            Err("usage: aoc2021 [--fail-over-budget | compare <rev-a> <rev-b> | dashboard]".into())
+        }
+    }
+}
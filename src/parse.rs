@@ -0,0 +1,359 @@
+//! Helpers for turning puzzle input into values, with errors that point at the
+//! offending text.
+//!
+//! Errors are positioned relative to the text they were produced from, so a
+//! helper run on a single line reports line 1. [`parse_lines`] and
+//! [`parse_blocks`] shift the errors of the closures they call so that they
+//! end up relative to the whole input.
+
+use std::{any::type_name, error::Error, fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line of the offending text.
+    pub line: usize,
+    /// 1-based column, in characters, of the offending text.
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// An error pointing at `token`, which must be a slice of `text`.
+    pub fn at(text: &str, token: &str, message: impl Into<String>) -> ParseError {
+        let offset = (token.as_ptr() as usize)
+            .saturating_sub(text.as_ptr() as usize)
+            .min(text.len());
+        let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        ParseError::new(
+            text[..offset].matches('\n').count() + 1,
+            text[line_start..offset].chars().count() + 1,
+            message,
+        )
+    }
+
+    /// Repositions an error produced from `token`, a slice of `text`, so that
+    /// it is relative to `text` instead.
+    pub fn within(self, text: &str, token: &str) -> ParseError {
+        let start = ParseError::at(text, token, self.message);
+        if self.line == 1 {
+            ParseError::new(start.line, start.column + self.column - 1, start.message)
+        } else {
+            ParseError::new(start.line + self.line - 1, self.column, start.message)
+        }
+    }
+
+    /// Moves this error down by `lines`, for when the text it was produced
+    /// from started on a later line of some larger input.
+    pub fn offset_lines(mut self, lines: usize) -> ParseError {
+        self.line += lines;
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses `token`, a slice of `text`, reporting where it was if it is not a
+/// valid `T`.
+pub fn parse_token<T: FromStr>(text: &str, token: &str) -> Result<T, ParseError> {
+    let token = token.trim();
+    token.parse().map_err(|_| {
+        let expected = type_name::<T>().rsplit("::").next().unwrap_or_default();
+        ParseError::at(
+            text,
+            token,
+            format!("expected {}, found {:?}", expected, token),
+        )
+    })
+}
+
+/// Parses every line with `f`, numbering any error by the line it came from.
+pub fn parse_lines<'a, T>(
+    input: &'a str,
+    mut f: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| f(line).map_err(|e| e.offset_lines(i)))
+        .collect()
+}
+
+/// Parses the `sep`-separated values of `text`, e.g. `3,4,3,1,2`.
+pub fn parse_separated<T: FromStr>(text: &str, sep: &str) -> Result<Vec<T>, ParseError> {
+    text.split(sep).map(|s| parse_token(text, s)).collect()
+}
+
+/// Every integer in `line`, including negative ones, skipping whatever
+/// separates them. A `-` only counts as a sign when it does not follow a
+/// digit, so `3-5` yields `[3, 5]`. Fails on integers that do not fit in
+/// an `i64`.
+pub fn ints(line: &str) -> Result<Vec<i64>, ParseError> {
+    let bytes = line.as_bytes();
+    let mut ints = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let negative = bytes[i] == b'-'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+            && (i == 0 || !bytes[i - 1].is_ascii_digit());
+        if !negative && !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        ints.push(parse_token(line, &line[start..i])?);
+    }
+    Ok(ints)
+}
+
+/// Splits `input` into lines with their 0-based line numbers, grouped into
+/// blocks separated by blank lines.
+fn numbered_blocks(input: &str) -> Vec<(usize, &str)> {
+    let mut blocks = vec![];
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+    for (i, line) in input.split_inclusive('\n').enumerate() {
+        let blank = line.trim().is_empty();
+        match (start, blank) {
+            (None, false) => start = Some((i, offset)),
+            (Some((n, begin)), true) => {
+                blocks.push((n, input[begin..offset].trim_end()));
+                start = None;
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if let Some((n, begin)) = start {
+        blocks.push((n, input[begin..].trim_end()));
+    }
+    blocks
+}
+
+/// The groups of lines in `input` that are separated by blank lines, such as
+/// the boards in day 04. Lines holding only whitespace count as blank.
+pub fn blocks(input: &str) -> impl Iterator<Item = &str> {
+    numbered_blocks(input).into_iter().map(|(_, block)| block)
+}
+
+/// Parses every blank-line separated block with `f`, numbering any error by
+/// its line in `input`.
+pub fn parse_blocks<'a, T>(
+    input: &'a str,
+    mut f: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    numbered_blocks(input)
+        .into_iter()
+        .map(|(n, block)| f(block).map_err(|e| e.offset_lines(n)))
+        .collect()
+}
+
+/// Splits `line` according to `pattern`, in which each `{}` stands for a
+/// field and everything else must match literally. Fields are trimmed.
+///
+/// ```
+/// # use aoc2021::parse::scan_fields;
+/// let fields = scan_fields("0,9 -> 5,9", "{},{} -> {},{}").unwrap();
+/// assert_eq!(fields, vec!["0", "9", "5", "9"]);
+/// ```
+pub fn scan_fields<'a>(line: &'a str, pattern: &str) -> Result<Vec<&'a str>, ParseError> {
+    let mut literals = pattern.split("{}");
+    let mut rest = line;
+
+    let prefix = literals.next().unwrap_or_default();
+    rest = rest
+        .strip_prefix(prefix)
+        .ok_or_else(|| ParseError::at(line, rest, format!("expected {:?}", prefix)))?;
+
+    let mut fields = vec![];
+    for literal in literals {
+        let end = if literal.is_empty() {
+            rest.len()
+        } else {
+            rest.find(literal).ok_or_else(|| {
+                ParseError::at(line, rest, format!("expected {:?} after here", literal))
+            })?
+        };
+        fields.push(rest[..end].trim());
+        rest = &rest[end + literal.len()..];
+    }
+
+    if !rest.is_empty() {
+        return Err(ParseError::at(
+            line,
+            rest,
+            format!("unexpected trailing {:?}", rest),
+        ));
+    }
+    Ok(fields)
+}
+
+/// Like [`scan_fields`], parsing each of the `N` fields as a `T`.
+///
+/// ```
+/// # use aoc2021::parse::scan;
+/// let [x1, y1, x2, y2] = scan::<i32, 4>("0,9 -> 5,9", "{},{} -> {},{}").unwrap();
+/// assert_eq!((x1, y1, x2, y2), (0, 9, 5, 9));
+/// ```
+pub fn scan<T: FromStr, const N: usize>(line: &str, pattern: &str) -> Result<[T; N], ParseError> {
+    let fields = scan_fields(line, pattern)?;
+    assert_eq!(
+        fields.len(),
+        N,
+        "pattern {:?} does not have {} fields",
+        pattern,
+        N
+    );
+    let values = fields
+        .into_iter()
+        .map(|field| parse_token(line, field))
+        .collect::<Result<Vec<T>, _>>()?;
+    Ok(values
+        .try_into()
+        .unwrap_or_else(|_| unreachable!("field count already checked")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_tokens() {
+        let text = "abc\nde fg\nh";
+        assert_eq!(
+            ParseError::at(text, &text[0..1], "x"),
+            ParseError::new(1, 1, "x")
+        );
+        assert_eq!(
+            ParseError::at(text, &text[7..9], "x"),
+            ParseError::new(2, 4, "x")
+        );
+        assert_eq!(
+            ParseError::at(text, &text[10..], "x"),
+            ParseError::new(3, 1, "x")
+        );
+        assert_eq!(
+            ParseError::new(2, 4, "bad").to_string(),
+            "line 2, column 4: bad"
+        );
+    }
+
+    #[test]
+    fn repositions_errors() {
+        let text = "ab\ncd ef";
+        let token = &text[6..];
+        assert_eq!(
+            ParseError::new(1, 2, "x").within(text, token),
+            ParseError::new(2, 5, "x")
+        );
+        assert_eq!(
+            ParseError::new(2, 2, "x").within(text, token),
+            ParseError::new(3, 2, "x")
+        );
+    }
+
+    #[test]
+    fn parses_tokens() {
+        let line = "move 12 x";
+        assert_eq!(parse_token::<u32>(line, &line[5..7]), Ok(12));
+        assert_eq!(
+            parse_token::<u32>(line, &line[8..]),
+            Err(ParseError::new(1, 9, "expected u32, found \"x\""))
+        );
+    }
+
+    #[test]
+    fn parses_lines() {
+        assert_eq!(
+            parse_lines("1\n2\n3", |line| parse_token::<i32>(line, line)),
+            Ok(vec![1, 2, 3])
+        );
+        assert_eq!(
+            parse_lines("1\n2\n 3x", |line| parse_token::<i32>(line, line)),
+            Err(ParseError::new(3, 2, "expected i32, found \"3x\""))
+        );
+    }
+
+    #[test]
+    fn parses_separated() {
+        assert_eq!(parse_separated("3,4,3,1,2", ","), Ok(vec![3, 4, 3, 1, 2]));
+        assert_eq!(
+            parse_separated::<u8>("3,4,,1", ","),
+            Err(ParseError::new(1, 5, "expected u8, found \"\""))
+        );
+    }
+
+    #[test]
+    fn extracts_ints() {
+        assert_eq!(ints("0,9 -> 5,9"), Ok(vec![0, 9, 5, 9]));
+        assert_eq!(ints("x=-20..30, y=-5"), Ok(vec![-20, 30, -5]));
+        assert_eq!(ints("3-5 - -2"), Ok(vec![3, 5, -2]));
+        assert_eq!(ints("no numbers"), Ok(vec![]));
+        assert_eq!(
+            ints("1, 99999999999999999999"),
+            Err(ParseError::new(
+                1,
+                4,
+                "expected i64, found \"99999999999999999999\""
+            ))
+        );
+    }
+
+    #[test]
+    fn splits_blocks() {
+        let input = "1,2\n\na b\nc d\n   \ne f\n";
+        assert_eq!(
+            blocks(input).collect::<Vec<_>>(),
+            vec!["1,2", "a b\nc d", "e f"]
+        );
+        assert_eq!(
+            parse_blocks(input, |block| match block {
+                "e f" => Err(ParseError::at(block, &block[2..], "no f")),
+                _ => Ok(block.len()),
+            }),
+            Err(ParseError::new(6, 3, "no f"))
+        );
+    }
+
+    #[test]
+    fn scans_patterns() {
+        assert_eq!(
+            scan::<i32, 4>("0,9 -> 5,9", "{},{} -> {},{}"),
+            Ok([0, 9, 5, 9])
+        );
+        assert_eq!(scan_fields("start-A", "{}-{}"), Ok(vec!["start", "A"]));
+        assert_eq!(
+            scan_fields("0,9 => 5,9", "{},{} -> {},{}"),
+            Err(ParseError::new(1, 3, "expected \" -> \" after here"))
+        );
+        assert_eq!(
+            scan::<i32, 2>("forward 5", "up {}"),
+            Err(ParseError::new(1, 1, "expected \"up \""))
+        );
+        assert_eq!(
+            scan::<i32, 2>("1,a", "{},{}"),
+            Err(ParseError::new(1, 3, "expected i32, found \"a\""))
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+/// The integer types points and vectors can be built from.
+pub trait Coord:
+    Copy + Debug + Default + Hash + Ord + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs_diff(self, other: Self) -> Self {
+        if self > other {
+            self - other
+        } else {
+            other - self
+        }
+    }
+}
+
+/// Coordinates that can point in any direction.
+pub trait SignedCoord: Coord + Neg<Output = Self> {
+    const NEG_ONE: Self;
+
+    fn signum(self) -> Self {
+        match self.cmp(&Self::ZERO) {
+            std::cmp::Ordering::Less => Self::NEG_ONE,
+            std::cmp::Ordering::Equal => Self::ZERO,
+            std::cmp::Ordering::Greater => Self::ONE,
+        }
+    }
+}
+
+macro_rules! impl_coord {
+    ($($t:ty),*) => {
+        $(impl Coord for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+        })*
+    };
+}
+
+impl_coord!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_signed_coord {
+    ($($t:ty),*) => {
+        $(impl SignedCoord for $t {
+            const NEG_ONE: Self = -1;
+        })*
+    };
+}
+
+impl_signed_coord!(i8, i16, i32, i64, i128, isize);
+
+/// A 2D point, with `y` growing downwards as in the puzzle inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// A 3D point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// The difference between two points.
+pub type Vector<T> = Point<T>;
+pub type Vector3<T> = Point3<T>;
+
+macro_rules! impl_ops {
+    ($point:ident { $($field:ident),* }) => {
+        impl<T: Coord> Add for $point<T> {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                $point { $($field: self.$field + other.$field),* }
+            }
+        }
+
+        impl<T: Coord> Sub for $point<T> {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                $point { $($field: self.$field - other.$field),* }
+            }
+        }
+
+        impl<T: Coord> AddAssign for $point<T> {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl<T: Coord> SubAssign for $point<T> {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+
+        impl<T: Coord> Mul<T> for $point<T> {
+            type Output = Self;
+
+            fn mul(self, scale: T) -> Self {
+                $point { $($field: self.$field * scale),* }
+            }
+        }
+
+        impl<T: SignedCoord> Neg for $point<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                $point { $($field: -self.$field),* }
+            }
+        }
+
+        impl<T: Coord> $point<T> {
+            pub const ORIGIN: Self = $point { $($field: T::ZERO),* };
+
+            /// The taxicab distance between two points.
+            pub fn manhattan(self, other: Self) -> T {
+                T::ZERO $(+ self.$field.abs_diff(other.$field))*
+            }
+
+            /// The number of king moves between two points.
+            pub fn chebyshev(self, other: Self) -> T {
+                T::ZERO $(.max(self.$field.abs_diff(other.$field)))*
+            }
+
+            /// Converts to another coordinate type, or `None` if any
+            /// coordinate does not fit.
+            pub fn try_cast<U: TryFrom<T>>(self) -> Option<$point<U>> {
+                Some($point { $($field: U::try_from(self.$field).ok()?),* })
+            }
+        }
+
+        impl<T: SignedCoord> $point<T> {
+            /// A vector of at most one step along each axis in this vector's
+            /// direction.
+            pub fn signum(self) -> Self {
+                $point { $($field: self.$field.signum()),* }
+            }
+        }
+    };
+}
+
+impl_ops!(Point { x, y });
+impl_ops!(Point3 { x, y, z });
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+}
+
+impl<T: SignedCoord> Point<T> {
+    pub const UP: Self = Point::new(T::ZERO, T::NEG_ONE);
+    pub const DOWN: Self = Point::new(T::ZERO, T::ONE);
+    pub const LEFT: Self = Point::new(T::NEG_ONE, T::ZERO);
+    pub const RIGHT: Self = Point::new(T::ONE, T::ZERO);
+
+    /// Unit vectors towards the four orthogonal neighbours, in reading order.
+    pub const ORTHOGONAL: [Self; 4] = [Self::UP, Self::LEFT, Self::RIGHT, Self::DOWN];
+
+    /// Unit vectors towards all eight neighbours, in reading order.
+    pub const ADJACENT: [Self; 8] = [
+        Point::new(T::NEG_ONE, T::NEG_ONE),
+        Self::UP,
+        Point::new(T::ONE, T::NEG_ONE),
+        Self::LEFT,
+        Self::RIGHT,
+        Point::new(T::NEG_ONE, T::ONE),
+        Self::DOWN,
+        Point::new(T::ONE, T::ONE),
+    ];
+
+    /// Turns 90° clockwise as seen on screen, e.g. `RIGHT` becomes `DOWN`.
+    pub fn rotate_right(self) -> Self {
+        Point::new(-self.y, self.x)
+    }
+
+    /// Turns 90° counter-clockwise as seen on screen, e.g. `RIGHT` becomes
+    /// `UP`.
+    pub fn rotate_left(self) -> Self {
+        Point::new(self.y, -self.x)
+    }
+}
+
+impl Point<usize> {
+    /// Moves this point by `delta`, or `None` if that would go negative.
+    pub fn offset(self, delta: Vector<isize>) -> Option<Point<usize>> {
+        Some(Point {
+            x: self.x.checked_add_signed(delta.x)?,
+            y: self.y.checked_add_signed(delta.y)?,
+        })
+    }
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Point3<T> {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T: SignedCoord> Point3<T> {
+    /// Unit vectors towards the six face neighbours.
+    pub const ORTHOGONAL: [Self; 6] = [
+        Point3::new(T::NEG_ONE, T::ZERO, T::ZERO),
+        Point3::new(T::ONE, T::ZERO, T::ZERO),
+        Point3::new(T::ZERO, T::NEG_ONE, T::ZERO),
+        Point3::new(T::ZERO, T::ONE, T::ZERO),
+        Point3::new(T::ZERO, T::ZERO, T::NEG_ONE),
+        Point3::new(T::ZERO, T::ZERO, T::ONE),
+    ];
+
+    /// Rotates 90° about the x axis, taking +y to +z.
+    pub fn rotate_x(self) -> Self {
+        Point3::new(self.x, -self.z, self.y)
+    }
+
+    /// Rotates 90° about the y axis, taking +z to +x.
+    pub fn rotate_y(self) -> Self {
+        Point3::new(self.z, self.y, -self.x)
+    }
+
+    /// Rotates 90° about the z axis, taking +x to +y.
+    pub fn rotate_z(self) -> Self {
+        Point3::new(-self.y, self.x, self.z)
+    }
+
+    /// This point under each of the 24 rotations of a cube, starting with
+    /// the identity.
+    pub fn rotations(self) -> [Self; 24] {
+        let mut rotations = [self; 24];
+        // Point each of the six faces along +x, then spin about x.
+        let facings = [
+            self,
+            self.rotate_z(),
+            self.rotate_z().rotate_z(),
+            self.rotate_z().rotate_z().rotate_z(),
+            self.rotate_y(),
+            self.rotate_y().rotate_y().rotate_y(),
+        ];
+        for (i, facing) in facings.into_iter().enumerate() {
+            let mut p = facing;
+            for j in 0..4 {
+                rotations[i * 4 + j] = p;
+                p = p.rotate_x();
+            }
+        }
+        rotations
+    }
+}
+
+impl<T: Coord> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Point<T> {
+        Point { x, y }
+    }
+}
+
+impl<T: Coord> From<Point<T>> for (T, T) {
+    fn from(p: Point<T>) -> (T, T) {
+        (p.x, p.y)
+    }
+}
+
+impl<T: Coord> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Point3<T> {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T: Coord> From<Point3<T>> for (T, T, T) {
+    fn from(p: Point3<T>) -> (T, T, T) {
+        (p.x, p.y, p.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn adds_subtracts_and_scales() {
+        let p = Point::new(3, -2);
+        assert_eq!(p + Point::new(1, 1), Point::new(4, -1));
+        assert_eq!(p - Point::new(1, 1), Point::new(2, -3));
+        assert_eq!(p * 3, Point::new(9, -6));
+        assert_eq!(-p, Point::new(-3, 2));
+
+        let mut q = Point3::new(1, 2, 3);
+        q += Point3::new(1, 1, 1);
+        q -= Point3::new(0, 0, 5);
+        assert_eq!(q, Point3::new(2, 3, -1));
+    }
+
+    #[test]
+    fn measures_distances() {
+        let a = Point::new(1, 5);
+        let b = Point::new(4, 1);
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+
+        let a = Point3::<u32>::new(1, 5, 2);
+        let b = Point3::new(4, 1, 10);
+        assert_eq!(a.manhattan(b), 15);
+        assert_eq!(a.chebyshev(b), 8);
+        assert_eq!(Point3::<u32>::ORIGIN.manhattan(a), 8);
+    }
+
+    #[test]
+    fn rotates_in_2d() {
+        assert_eq!(Point::<i32>::RIGHT.rotate_right(), Point::DOWN);
+        assert_eq!(Point::<i32>::RIGHT.rotate_left(), Point::UP);
+        assert_eq!(
+            Point::new(2, 1).rotate_right().rotate_left(),
+            Point::new(2, 1)
+        );
+        assert_eq!(
+            Point::<i64>::ORTHOGONAL.map(Point::rotate_right),
+            [Point::RIGHT, Point::UP, Point::DOWN, Point::LEFT]
+        );
+    }
+
+    #[test]
+    fn directions_are_unit_steps() {
+        let origin = Point::<i32>::ORIGIN;
+        assert!(Point::ORTHOGONAL.iter().all(|&d| origin.manhattan(d) == 1));
+        assert!(Point::ADJACENT.iter().all(|&d| origin.chebyshev(d) == 1));
+        assert_eq!(Point::new(-7, 0).signum(), Point::LEFT);
+        assert_eq!(Point3::new(5, 0, -3).signum(), Point3::new(1, 0, -1));
+    }
+
+    #[test]
+    fn rotates_in_3d() {
+        let p = Point3::new(1, 2, 3);
+        assert_eq!(p.rotate_x().rotate_x().rotate_x().rotate_x(), p);
+        assert_eq!(Point3::new(0, 1, 0).rotate_x(), Point3::new(0, 0, 1));
+        assert_eq!(Point3::new(0, 0, 1).rotate_y(), Point3::new(1, 0, 0));
+        assert_eq!(Point3::new(1, 0, 0).rotate_z(), Point3::new(0, 1, 0));
+
+        let rotations = p.rotations();
+        assert_eq!(rotations[0], p);
+        assert_eq!(rotations.iter().collect::<HashSet<_>>().len(), 24);
+        assert!(rotations.iter().all(|r| r.manhattan(Point3::ORIGIN) == 6));
+    }
+
+    #[test]
+    fn converts() {
+        assert_eq!(Point::from((1, 2)), Point::new(1, 2));
+        assert_eq!(<(i32, i32)>::from(Point::new(1, 2)), (1, 2));
+        assert_eq!(Point3::from((1, 2, 3)), Point3::new(1, 2, 3));
+        assert_eq!(
+            Point::new(1i64, 2).try_cast::<usize>(),
+            Some(Point::new(1, 2))
+        );
+        assert_eq!(Point::new(-1i64, 2).try_cast::<usize>(), None);
+        assert_eq!(
+            Point::new(3usize, 0).offset(Point::LEFT),
+            Some(Point::new(2, 0))
+        );
+        assert_eq!(Point::new(3usize, 0).offset(Point::UP), None);
+    }
+}
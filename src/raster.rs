@@ -0,0 +1,232 @@
+use crate::point::{Point, SignedCoord, Vector};
+
+/// The lattice points on a line segment, from `start` to `end` inclusive,
+/// yielded lazily using Bresenham's algorithm. Horizontal, vertical and 45°
+/// lines visit every point they pass through; other lines visit the points
+/// closest to them, one per step along their longer axis.
+#[derive(Debug, Clone)]
+pub struct Raster<T> {
+    current: Point<T>,
+    end: Point<T>,
+    step: Vector<T>,
+    // The extent along each axis, with `y` negated, and the running error
+    // term, as in the usual all-octant formulation.
+    delta: Vector<T>,
+    err: T,
+    done: bool,
+}
+
+impl<T: SignedCoord> Raster<T> {
+    pub fn new(start: Point<T>, end: Point<T>) -> Raster<T> {
+        let delta = Point::new(start.x.abs_diff(end.x), -start.y.abs_diff(end.y));
+        Raster {
+            current: start,
+            end,
+            step: (end - start).signum(),
+            delta,
+            err: delta.x + delta.y,
+            done: false,
+        }
+    }
+
+    /// Restricts the line to the points within the box with corners `min` and
+    /// `max`, inclusive, jumping directly to the part inside it.
+    pub fn clip(mut self, min: Point<T>, max: Point<T>) -> Raster<T>
+    where
+        T: Into<i128> + TryFrom<i128>,
+    {
+        if self.done {
+            return self;
+        }
+        let wide = |p: Point<T>| Point::new(p.x.into(), p.y.into());
+        let narrow = |v: i128| T::try_from(v).unwrap_or_else(|_| unreachable!());
+
+        // Every step moves once along the longer axis, and after `k` steps
+        // the line has moved `minor(k)` times along the shorter one, the
+        // nearest whole number to the exact line with halves rounded down.
+        let (origin, direction, delta) = (wide(self.current), wide(self.step), wide(self.delta));
+        let x_major = delta.x > -delta.y;
+        let orient = |p: Point<i128>| if x_major { p } else { Point::new(p.y, p.x) };
+        let (start, step) = (orient(origin), orient(direction));
+        let (min, max) = (orient(wide(min)), orient(wide(max)));
+        let extent = orient(Point::new(delta.x, -delta.y));
+        let (steps, shorter) = (extent.x, extent.y);
+        let minor = |k: i128| match steps {
+            0 => 0,
+            _ => (2 * k * shorter + steps - 1) / (2 * steps),
+        };
+
+        // The range of moves along an axis that keep it within the box.
+        let axis = |start: i128, step: i128, min: i128, max: i128| match step {
+            1 => (min - start, max - start),
+            -1 => (start - max, start - min),
+            _ if (min..=max).contains(&start) => (i128::MIN, i128::MAX),
+            _ => (1, 0),
+        };
+        let (major_lo, major_hi) = axis(start.x, step.x, min.x, max.x);
+        let (minor_lo, minor_hi) = axis(start.y, step.y, min.y, max.y);
+        // `minor` never decreases, so the steps it keeps in range are found by
+        // bisection.
+        let lo = first_step(steps, |k| minor(k) >= minor_lo).max(major_lo.max(0));
+        let hi = (first_step(steps, |k| minor(k) > minor_hi) - 1).min(major_hi.min(steps));
+        if lo > hi {
+            self.done = true;
+            return self;
+        }
+
+        // The moves along each axis after `k` steps, and where they lead.
+        let moves = |k: i128| orient(Point::new(k, minor(k)));
+        let at = |k: i128| {
+            let moves = moves(k);
+            Point::new(
+                origin.x + moves.x * direction.x,
+                origin.y + moves.y * direction.y,
+            )
+        };
+        let (current, end) = (at(lo), at(hi));
+        let err = self.err.into() + moves(lo).x * delta.y + moves(lo).y * delta.x;
+        self.current = Point::new(narrow(current.x), narrow(current.y));
+        self.end = Point::new(narrow(end.x), narrow(end.y));
+        self.err = narrow(err);
+        self
+    }
+
+    fn advance(&mut self) {
+        if self.current == self.end {
+            self.done = true;
+            return;
+        }
+        let e2 = self.err + self.err;
+        if e2 > self.delta.y {
+            self.err = self.err + self.delta.y;
+            self.current.x = self.current.x + self.step.x;
+        }
+        if e2 < self.delta.x {
+            self.err = self.err + self.delta.x;
+            self.current.y = self.current.y + self.step.y;
+        }
+    }
+}
+
+/// The first of the steps `0..=steps` for which `pred` holds, or `steps + 1`
+/// if none does, given that it holds for every step after one it holds for.
+fn first_step(steps: i128, pred: impl Fn(i128) -> bool) -> i128 {
+    let (mut lo, mut hi) = (0, steps + 1);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    lo
+}
+
+impl<T: SignedCoord> Iterator for Raster<T> {
+    type Item = Point<T>;
+
+    fn next(&mut self) -> Option<Point<T>> {
+        if self.done {
+            return None;
+        }
+        let p = self.current;
+        self.advance();
+        Some(p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
+        Raster::new(start.into(), end.into())
+            .map(Into::into)
+            .collect()
+    }
+
+    fn clipped(start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
+        Raster::new(start.into(), end.into())
+            .clip(Point::new(0, 0), Point::new(4, 4))
+            .map(Into::into)
+            .collect()
+    }
+
+    #[test]
+    fn rasterizes_straight_lines() {
+        assert_eq!(points((3, 1), (0, 1)), [(3, 1), (2, 1), (1, 1), (0, 1)]);
+        assert_eq!(points((2, 2), (2, 4)), [(2, 2), (2, 3), (2, 4)]);
+        assert_eq!(points((5, 5), (8, 2)), [(5, 5), (6, 4), (7, 3), (8, 2)]);
+        assert_eq!(points((1, 1), (1, 1)), [(1, 1)]);
+    }
+
+    #[test]
+    fn rasterizes_arbitrary_lines() {
+        assert_eq!(
+            points((0, 0), (4, 2)),
+            [(0, 0), (1, 0), (2, 1), (3, 1), (4, 2)]
+        );
+        assert_eq!(points((0, 0), (-1, 3)), [(0, 0), (0, 1), (-1, 2), (-1, 3)]);
+        let reversed = points((4, 2), (0, 0));
+        assert_eq!(reversed.first(), Some(&(4, 2)));
+        assert_eq!(reversed.last(), Some(&(0, 0)));
+        assert_eq!(reversed.len(), 5);
+    }
+
+    #[test]
+    fn clips_to_bounds() {
+        assert_eq!(
+            clipped((-3, 2), (9, 2)),
+            [(0, 2), (1, 2), (2, 2), (3, 2), (4, 2)]
+        );
+        assert_eq!(clipped((6, 6), (2, 2)), [(4, 4), (3, 3), (2, 2)]);
+        assert_eq!(clipped((5, 0), (5, 4)), []);
+        assert_eq!(clipped((-2, 3), (3, -2)), [(0, 1), (1, 0)]);
+        assert_eq!(
+            clipped((-2, -1), (6, 3)),
+            [(0, 0), (1, 0), (2, 1), (3, 1), (4, 2)]
+        );
+        assert_eq!(clipped((-4, 6), (-2, 7)), []);
+    }
+
+    #[test]
+    fn clips_large_lines_without_visiting_every_point() {
+        let far = 1 << 60;
+        let points = Raster::new(Point::new(-far, 1i64), Point::new(far, 1))
+            .clip(Point::new(0, 0), Point::new(2, 2))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            points,
+            [Point::new(0, 1), Point::new(1, 1), Point::new(2, 1)]
+        );
+
+        let shallow = Raster::new(Point::new(-far, 0i64), Point::new(far, 1))
+            .clip(Point::new(-2, -2), Point::new(2, 2))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            shallow,
+            [
+                Point::new(-2, 0),
+                Point::new(-1, 0),
+                Point::new(0, 0),
+                Point::new(1, 1),
+                Point::new(2, 1)
+            ]
+        );
+        let missing = Raster::new(Point::new(-far, 5i64), Point::new(far, 9))
+            .clip(Point::new(-2, -2), Point::new(2, 2));
+        assert_eq!(missing.count(), 0);
+    }
+
+    #[test]
+    fn clips_to_the_points_inside_the_box() {
+        for (x1, y1, x2, y2) in itertools::iproduct!(-6..=10, -6..=10, -6..=10, -6..=10) {
+            let inside = points((x1, y1), (x2, y2))
+                .into_iter()
+                .filter(|&(x, y)| (0..=4).contains(&x) && (0..=4).contains(&y))
+                .collect::<Vec<_>>();
+            assert_eq!(clipped((x1, y1), (x2, y2)), inside);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+pub mod budget;
+pub mod compare;
+pub mod dashboard;
+
+use itertools::Itertools;
+use std::{error::Error, fs, path::Path, process::Command};
+
+/// The answers and timing a day binary printed to stdout.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DayOutput {
+    pub part_one: String,
+    pub part_two: String,
+    pub micros: usize,
+}
+
+/// Returns the names of every day binary under `root/src/bin`, sorted.
+pub fn days(root: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    Ok(fs::read_dir(root.join("src/bin"))?
+        .filter_map(|p| p.ok()?.path().file_stem()?.to_str().map(str::to_string))
+        .sorted()
+        .collect())
+}
+
+fn extract_microseconds(output: &str) -> Result<usize, Box<dyn Error>> {
+    let out = output.lines().last().ok_or("no output")?;
+    let time = out
+        .strip_prefix("Time: ")
+        .ok_or_else(|| format!("missing \"Time:\" line, found {:?}", out))?;
+    if let Some(ms) = time.strip_suffix("ms") {
+        Ok(ms.parse::<usize>()? * 1000)
+    } else if let Some(micros) = time.strip_suffix("μs") {
+        Ok(micros.parse()?)
+    } else {
+        Err(format!("expected a time in ms or μs, found {:?}", time).into())
+    }
+}
+
+fn extract_answer(output: &str, prefix: &str) -> Result<String, Box<dyn Error>> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix(prefix))
+        .map(str::to_string)
+        .ok_or_else(|| format!("missing \"{}\" line", prefix.trim()).into())
+}
+
+pub fn parse_output(output: &str) -> Result<DayOutput, Box<dyn Error>> {
+    Ok(DayOutput {
+        part_one: extract_answer(output, "Part one: ")?,
+        part_two: extract_answer(output, "Part two: ")?,
+        micros: extract_microseconds(output)?,
+    })
+}
+
+/// Runs a day through `cargo run --release` and returns its raw stdout. If the
+/// day fails to build or panics, the error carries its stderr.
+pub fn run_day(day: &str) -> Result<String, Box<dyn Error>> {
+    let cmd = Command::new("cargo")
+        .args(["run", "--release", "--bin", day])
+        .output()?;
+    if !cmd.status.success() {
+        return Err(String::from_utf8_lossy(&cmd.stderr).trim_end().into());
+    }
+    Ok(String::from_utf8(cmd.stdout)?)
+}
+
+/// Runs a day's unit tests and returns whether they passed along with
+/// everything cargo printed.
+pub fn test_day(day: &str) -> Result<(bool, String), Box<dyn Error>> {
+    let cmd = Command::new("cargo")
+        .args(["test", "--bin", day])
+        .output()?;
+    let mut output = String::from_utf8(cmd.stdout)?;
+    output.push_str(&String::from_utf8_lossy(&cmd.stderr));
+    Ok((cmd.status.success(), output))
+}
+
+pub fn format_micros(micros: usize) -> String {
+    if micros >= 1000 {
+        format!("{}ms", micros / 1000)
+    } else {
+        format!("{}μs", micros)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_day_output() {
+        let output = "Part one: 7\nPart two: 5\nTime: 12ms";
+        assert_eq!(
+            parse_output(output).unwrap(),
+            DayOutput {
+                part_one: "7".to_string(),
+                part_two: "5".to_string(),
+                micros: 12000,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_day_output_in_microseconds() {
+        let output = "debug noise\nPart one: 7\nPart two: 5\nTime: 340μs";
+        assert_eq!(parse_output(output).unwrap().micros, 340);
+    }
+
+    #[test]
+    fn rejects_output_without_answers() {
+        assert!(parse_output("Time: 340μs").is_err());
+    }
+
+    #[test]
+    fn rejects_output_without_a_time() {
+        for output in [
+            "Part one: 7\nPart two: 5\nok",
+            "Part one: 7\nPart two: 5\nTime: 3s",
+        ] {
+            assert!(parse_output(output).is_err());
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use super::format_micros;
+use crossterm::style::Stylize;
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+/// Time budgets in microseconds, read from a file of `<day> <duration>` lines
+/// where `<day>` is a day name or `total`, e.g.
+///
+/// ```text
+/// # Everything under one second
+/// total 1s
+/// 12 300ms
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Budgets {
+    pub total: Option<usize>,
+    pub days: HashMap<String, usize>,
+}
+
+fn parse_duration(s: &str) -> Option<usize> {
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let value = s[..split].parse::<usize>().ok()?;
+    let scale = match &s[split..] {
+        "us" | "μs" => 1,
+        "ms" => 1000,
+        "s" => 1_000_000,
+        _ => return None,
+    };
+    value.checked_mul(scale)
+}
+
+pub fn parse_budgets(input: &str) -> Result<Budgets, String> {
+    let mut budgets = Budgets::default();
+    for (n, line) in input.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (name, duration) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("line {}: expected `<day> <duration>`", n + 1))?;
+        let micros = parse_duration(duration.trim())
+            .ok_or_else(|| format!("line {}: invalid duration {:?}", n + 1, duration.trim()))?;
+        if name == "total" {
+            budgets.total = Some(micros);
+        } else {
+            budgets.days.insert(name.to_string(), micros);
+        }
+    }
+    Ok(budgets)
+}
+
+/// Loads budgets from `path`, or no budgets at all if the file is missing.
+pub fn load_budgets(path: &Path) -> Result<Budgets, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(Budgets::default());
+    }
+    Ok(parse_budgets(&fs::read_to_string(path)?)?)
+}
+
+/// Describes how much of `budget` was used, highlighting overruns.
+fn usage(micros: usize, budget: usize) -> String {
+    let percent = micros as f64 * 100.0 / budget.max(1) as f64;
+    let usage = format!(
+        "{} of {} ({:.1}%)",
+        format_micros(micros),
+        format_micros(budget),
+        percent
+    );
+    if micros > budget {
+        format!("{}", format!("{} OVER BUDGET", usage).red().bold())
+    } else {
+        usage
+    }
+}
+
+impl Budgets {
+    /// Checks that every budget is for one of `days`, so that a misspelt day
+    /// is not silently left unchecked.
+    pub fn check_days(&self, days: &[String]) -> Result<(), String> {
+        let mut unknown = self
+            .days
+            .keys()
+            .filter(|name| !days.contains(name))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        unknown.sort_unstable();
+        Err(format!(
+            "budgets for unknown days: {}; expected total or one of {}",
+            unknown.join(", "),
+            days.join(", ")
+        ))
+    }
+
+    /// Whether `day` took longer than its own budget.
+    pub fn day_over(&self, day: &str, micros: usize) -> bool {
+        self.days.get(day).is_some_and(|&budget| micros > budget)
+    }
+
+    pub fn total_over(&self, micros: usize) -> bool {
+        self.total.is_some_and(|budget| micros > budget)
+    }
+
+    /// A line describing `day`'s time against its budget, or its share of
+    /// the total budget if it has none of its own.
+    pub fn day_report(&self, day: &str, micros: usize) -> Option<String> {
+        match (self.days.get(day), self.total) {
+            (Some(&budget), _) => Some(format!("Budget: {}", usage(micros, budget))),
+            (None, Some(total)) => Some(format!(
+                "Budget: {:.1}% of total {}",
+                micros as f64 * 100.0 / total.max(1) as f64,
+                format_micros(total)
+            )),
+            (None, None) => None,
+        }
+    }
+
+    pub fn total_report(&self, micros: usize) -> Option<String> {
+        self.total
+            .map(|budget| format!("Total budget: {}", usage(micros, budget)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("250us"), Some(250));
+        assert_eq!(parse_duration("250μs"), Some(250));
+        assert_eq!(parse_duration("12ms"), Some(12_000));
+        assert_eq!(parse_duration("1s"), Some(1_000_000));
+        assert_eq!(parse_duration("12"), None);
+        assert_eq!(parse_duration("ms"), None);
+        assert_eq!(parse_duration("12min"), None);
+    }
+
+    #[test]
+    fn parses_budget_file() {
+        let budgets = parse_budgets("# comment\ntotal 1s\n\n05 20ms # slow\n12 300ms").unwrap();
+        assert_eq!(budgets.total, Some(1_000_000));
+        assert_eq!(budgets.days["05"], 20_000);
+        assert_eq!(budgets.days["12"], 300_000);
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        assert_eq!(
+            parse_budgets("total 1s\n05"),
+            Err("line 2: expected `<day> <duration>`".to_string())
+        );
+        assert_eq!(
+            parse_budgets("05 fast"),
+            Err("line 1: invalid duration \"fast\"".to_string())
+        );
+        assert_eq!(
+            parse_budgets("total 1s\n05 99999999999999s"),
+            Err("line 2: invalid duration \"99999999999999s\"".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_budgets_for_unknown_days() {
+        let days = ["05".to_string(), "12".to_string()];
+        assert_eq!(
+            parse_budgets("total 1s\n05 20ms")
+                .unwrap()
+                .check_days(&days),
+            Ok(())
+        );
+        assert_eq!(
+            parse_budgets("5 20ms\n12 1ms\nday12 1ms")
+                .unwrap()
+                .check_days(&days),
+            Err("budgets for unknown days: 5, day12; expected total or one of 05, 12".to_string())
+        );
+    }
+
+    #[test]
+    fn detects_overruns() {
+        let budgets = parse_budgets("total 1ms\n05 100us").unwrap();
+        assert!(budgets.day_over("05", 101));
+        assert!(!budgets.day_over("05", 100));
+        assert!(!budgets.day_over("06", 5000));
+        assert!(budgets.total_over(1001));
+    }
+}
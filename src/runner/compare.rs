@@ -0,0 +1,145 @@
+use super::{days, format_micros, parse_output, DayOutput};
+use std::{
+    collections::BTreeSet,
+    env,
+    error::Error,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// A detached git worktree checked out at some revision. The worktree is
+/// removed again when this is dropped.
+struct Worktree {
+    rev: String,
+    path: PathBuf,
+}
+
+impl Worktree {
+    fn checkout(repo: &Path, rev: &str, slot: usize) -> Result<Worktree, Box<dyn Error>> {
+        let path = env::temp_dir().join(format!("aoc2021-compare-{}-{}", std::process::id(), slot));
+        let status = Command::new("git")
+            .current_dir(repo)
+            .args(["worktree", "add", "--detach", "--quiet"])
+            .arg(&path)
+            .arg(rev)
+            .status()?;
+        if !status.success() {
+            return Err(format!("could not check out {}", rev).into());
+        }
+        Ok(Worktree {
+            rev: rev.to_string(),
+            path,
+        })
+    }
+
+    fn build(&self) -> Result<(), Box<dyn Error>> {
+        println!("Building {}...", self.rev);
+        let status = Command::new("cargo")
+            .current_dir(&self.path)
+            .args(["build", "--release", "--bins", "--quiet"])
+            .status()?;
+        if !status.success() {
+            return Err(format!("{} failed to build", self.rev).into());
+        }
+        Ok(())
+    }
+
+    fn run(&self, day: &str) -> Result<DayOutput, Box<dyn Error>> {
+        let cmd = Command::new(self.path.join("target/release").join(day))
+            .current_dir(&self.path)
+            .stderr(Stdio::null())
+            .output()?;
+        parse_output(&String::from_utf8(cmd.stdout)?)
+            .map_err(|e| format!("day {} at {}: {}", day, self.rev, e).into())
+    }
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        let _ = Command::new("git")
+            .current_dir(&self.path)
+            .args(["worktree", "remove", "--force"])
+            .arg(&self.path)
+            .status();
+    }
+}
+
+/// Describes how long `after` took relative to `before`, e.g. "2.50x faster".
+fn speedup(before: usize, after: usize) -> String {
+    let (before, after) = (before.max(1) as f64, after.max(1) as f64);
+    if before >= after {
+        format!("{:.2}x faster", before / after)
+    } else {
+        format!("{:.2}x slower", after / before)
+    }
+}
+
+fn print_day(day: &str, a: &DayOutput, b: &DayOutput) {
+    let answers = if a.part_one == b.part_one && a.part_two == b.part_two {
+        "answers equal"
+    } else {
+        "ANSWERS DIFFER"
+    };
+    println!(
+        "Day {}: {}, {} -> {} ({})",
+        day,
+        answers,
+        format_micros(a.micros),
+        format_micros(b.micros),
+        speedup(a.micros, b.micros)
+    );
+    if a.part_one != b.part_one {
+        println!("  Part one: {} -> {}", a.part_one, b.part_one);
+    }
+    if a.part_two != b.part_two {
+        println!("  Part two: {} -> {}", a.part_two, b.part_two);
+    }
+}
+
+/// Builds `rev_a` and `rev_b` in their own worktrees, runs every day in both
+/// and prints whether the answers agree along with the relative speed.
+pub fn compare(rev_a: &str, rev_b: &str) -> Result<(), Box<dyn Error>> {
+    let repo = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let a = Worktree::checkout(repo, rev_a, 0)?;
+    let b = Worktree::checkout(repo, rev_b, 1)?;
+    a.build()?;
+    b.build()?;
+
+    let days_a = days(&a.path)?.into_iter().collect::<BTreeSet<_>>();
+    let days_b = days(&b.path)?.into_iter().collect::<BTreeSet<_>>();
+    let (mut total_a, mut total_b) = (0, 0);
+    for day in days_a.union(&days_b) {
+        if !days_b.contains(day) {
+            println!("Day {}: only in {}", day, rev_a);
+            continue;
+        }
+        if !days_a.contains(day) {
+            println!("Day {}: only in {}", day, rev_b);
+            continue;
+        }
+
+        let (out_a, out_b) = (a.run(day)?, b.run(day)?);
+        print_day(day, &out_a, &out_b);
+        total_a += out_a.micros;
+        total_b += out_b.micros;
+    }
+    println!(
+        "Total time: {} -> {} ({})",
+        format_micros(total_a),
+        format_micros(total_b),
+        speedup(total_a, total_b)
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speedup_reports_direction() {
+        assert_eq!(speedup(300, 100), "3.00x faster");
+        assert_eq!(speedup(100, 250), "2.50x slower");
+        assert_eq!(speedup(0, 0), "1.00x faster");
+    }
+}
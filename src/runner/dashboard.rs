@@ -0,0 +1,448 @@
+use super::{days, format_micros, parse_output, run_day, test_day, DayOutput};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute, queue,
+    style::{Print, PrintStyledContent, Stylize},
+    terminal::{self, ClearType},
+};
+use std::{
+    error::Error,
+    io::{self, Stdout, Write},
+    path::Path,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+enum Status {
+    NotRun,
+    Queued,
+    Running,
+    Done(DayOutput),
+    Failed,
+}
+
+struct Day {
+    name: String,
+    status: Status,
+    output: String,
+    tests: Option<String>,
+}
+
+enum Job {
+    Run(usize, String),
+    Test(usize, String),
+}
+
+enum Update {
+    Started(usize),
+    Ran(usize, Result<String, String>),
+    Tested(usize, bool, String),
+}
+
+enum View {
+    List,
+    Output(usize),
+    Tests(usize),
+}
+
+/// Runs jobs one at a time so that concurrent days do not skew each other's
+/// timings.
+fn spawn_worker() -> (Sender<Job>, Receiver<Update>) {
+    let (job_tx, job_rx) = mpsc::channel::<Job>();
+    let (update_tx, update_rx) = mpsc::channel();
+    thread::spawn(move || {
+        for job in job_rx {
+            let update = match job {
+                Job::Run(i, day) => {
+                    let _ = update_tx.send(Update::Started(i));
+                    Update::Ran(i, run_day(&day).map_err(|e| e.to_string()))
+                }
+                Job::Test(i, day) => {
+                    let _ = update_tx.send(Update::Started(i));
+                    match test_day(&day) {
+                        Ok((passed, output)) => Update::Tested(i, passed, output),
+                        Err(e) => Update::Tested(i, false, e.to_string()),
+                    }
+                }
+            };
+            if update_tx.send(update).is_err() {
+                break;
+            }
+        }
+    });
+    (job_tx, update_rx)
+}
+
+/// The first of `rows` visible list entries after moving the selection to
+/// `selected`, scrolling from `top` only as far as needed to keep it in view.
+fn scroll_to_show(top: usize, selected: usize, rows: usize) -> usize {
+    top.min(selected).max((selected + 1).saturating_sub(rows))
+}
+
+/// Puts the terminal into raw mode on the alternate screen for as long as it
+/// lives, restoring it when dropped, or before a panic is reported.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new(out: &mut Stdout) -> io::Result<TerminalGuard> {
+        let report = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            TerminalGuard::restore();
+            report(info);
+        }));
+        terminal::enable_raw_mode()?;
+        execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(TerminalGuard)
+    }
+
+    fn restore() {
+        let _ = execute!(io::stdout(), terminal::LeaveAlternateScreen, cursor::Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        TerminalGuard::restore();
+    }
+}
+
+/// Pads or truncates `s` to exactly `width` characters.
+fn fit(s: &str, width: usize) -> String {
+    format!("{:<width$.width$}", s, width = width)
+}
+
+struct Dashboard {
+    days: Vec<Day>,
+    selected: usize,
+    /// The first day shown in the list.
+    top: usize,
+    /// The first line shown of a day's output or tests.
+    scroll: usize,
+    view: View,
+    jobs: Sender<Job>,
+}
+
+impl Dashboard {
+    /// Queues a run of a day, unless one is already queued or running.
+    fn queue_run(&mut self, i: usize) {
+        if let Status::Queued | Status::Running = self.days[i].status {
+            return;
+        }
+        self.days[i].status = Status::Queued;
+        let _ = self.jobs.send(Job::Run(i, self.days[i].name.clone()));
+    }
+
+    fn queue_tests(&mut self, i: usize) {
+        self.days[i].tests = Some("Running tests...".to_string());
+        let _ = self.jobs.send(Job::Test(i, self.days[i].name.clone()));
+    }
+
+    fn apply(&mut self, update: Update) {
+        match update {
+            Update::Started(i) => {
+                if let Status::Queued = self.days[i].status {
+                    self.days[i].status = Status::Running;
+                }
+            }
+            Update::Ran(i, Ok(output)) => {
+                let day = &mut self.days[i];
+                day.status = match parse_output(&output) {
+                    Ok(parsed) => Status::Done(parsed),
+                    Err(_) => Status::Failed,
+                };
+                day.output = output;
+            }
+            Update::Ran(i, Err(error)) => {
+                self.days[i].status = Status::Failed;
+                self.days[i].output = error;
+            }
+            Update::Tested(i, passed, output) => {
+                let verdict = if passed { "PASSED" } else { "FAILED" };
+                self.days[i].tests = Some(format!("Tests {}\n\n{}", verdict, output));
+            }
+        }
+    }
+
+    /// Handles a key press, returning false once the user asks to quit.
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        match (&self.view, code) {
+            (View::List, KeyCode::Char('q') | KeyCode::Esc) => return false,
+            (View::List, KeyCode::Up | KeyCode::Char('k')) => {
+                self.selected = self.selected.saturating_sub(1)
+            }
+            (View::List, KeyCode::Down | KeyCode::Char('j')) => {
+                self.selected = (self.selected + 1).min(self.days.len().saturating_sub(1))
+            }
+            (View::List, KeyCode::Char('a')) => {
+                (0..self.days.len()).for_each(|i| self.queue_run(i))
+            }
+            (View::List, KeyCode::Enter | KeyCode::Char('o')) => {
+                self.scroll = 0;
+                self.view = View::Output(self.selected);
+            }
+            (_, KeyCode::Char('r')) => self.queue_run(self.selected),
+            (_, KeyCode::Char('t')) => {
+                self.queue_tests(self.selected);
+                self.scroll = 0;
+                self.view = View::Tests(self.selected);
+            }
+            (_, KeyCode::Up | KeyCode::Char('k')) => self.scroll = self.scroll.saturating_sub(1),
+            (_, KeyCode::Down | KeyCode::Char('j')) => self.scroll += 1,
+            (_, KeyCode::Char('q') | KeyCode::Esc | KeyCode::Backspace) => self.view = View::List,
+            _ => {}
+        }
+        true
+    }
+
+    /// The title and text of the output or tests view.
+    fn text(&self) -> Option<(String, &str)> {
+        match self.view {
+            View::List => None,
+            View::Output(i) => {
+                let title = format!("Day {} output - r rerun, Esc back", self.days[i].name);
+                let text = match self.days[i].output.as_str() {
+                    "" => "Not run yet, press r to run.",
+                    output => output,
+                };
+                Some((title, text))
+            }
+            View::Tests(i) => {
+                let title = format!("Day {} tests - t rerun, Esc back", self.days[i].name);
+                Some((title, self.days[i].tests.as_deref().unwrap_or_default()))
+            }
+        }
+    }
+
+    fn draw(&mut self, out: &mut Stdout) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        // Keep the selection in view, and stop scrolling at the end of the
+        // text, however the terminal has been resized.
+        self.top = scroll_to_show(self.top, self.selected, height.saturating_sub(3).max(1));
+        if let Some((_, text)) = self.text() {
+            let last = text
+                .lines()
+                .count()
+                .saturating_sub(height.saturating_sub(1));
+            self.scroll = self.scroll.min(last);
+        }
+
+        queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        match self.text() {
+            None => self.draw_list(out, width, height)?,
+            Some((title, text)) => self.draw_text(out, &title, text, width, height)?,
+        }
+        out.flush()
+    }
+
+    fn draw_list(&self, out: &mut Stdout, width: usize, height: usize) -> io::Result<()> {
+        queue!(
+            out,
+            PrintStyledContent(
+                fit(
+                    "Day  Status    Part one         Part two         Time",
+                    width
+                )
+                .bold()
+            )
+        )?;
+        let max_micros = self
+            .days
+            .iter()
+            .filter_map(|d| match &d.status {
+                Status::Done(output) => Some(output.micros),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        let bar_width = width.saturating_sub(63);
+        let visible = self.days.iter().enumerate().skip(self.top);
+        for (line, (i, day)) in visible.take(height.saturating_sub(3)).enumerate() {
+            let row = match &day.status {
+                Status::Done(output) => {
+                    let bar = bar_width * output.micros / max_micros.max(1);
+                    format!(
+                        "{}   {} {} {} {:>9} {}",
+                        fit(&day.name, 2),
+                        fit("done", 9),
+                        fit(&output.part_one, 16),
+                        fit(&output.part_two, 16),
+                        format_micros(output.micros),
+                        "█".repeat(bar.max(1))
+                    )
+                }
+                status => {
+                    let status = match status {
+                        Status::NotRun => "not run",
+                        Status::Queued => "queued",
+                        Status::Running => "running",
+                        _ => "failed",
+                    };
+                    format!("{}   {}", fit(&day.name, 2), status)
+                }
+            };
+            let row = fit(&row, width);
+            let styled = match day.status {
+                Status::Done(_) => row.green(),
+                Status::Failed => row.red(),
+                Status::Queued | Status::Running => row.yellow(),
+                Status::NotRun => row.reset(),
+            };
+            let styled = if i == self.selected {
+                styled.reverse()
+            } else {
+                styled
+            };
+            queue!(
+                out,
+                cursor::MoveTo(0, line as u16 + 1),
+                PrintStyledContent(styled)
+            )?;
+        }
+
+        let total: usize = self
+            .days
+            .iter()
+            .filter_map(|d| match &d.status {
+                Status::Done(output) => Some(output.micros),
+                _ => None,
+            })
+            .sum();
+        queue!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(1) as u16),
+            Print(fit(
+                &format!(
+                    "Total time: {} | ↑/↓ select, r rerun, a run all, o output, t tests, q quit",
+                    format_micros(total)
+                ),
+                width
+            ))
+        )
+    }
+
+    fn draw_text(
+        &self,
+        out: &mut Stdout,
+        title: &str,
+        text: &str,
+        width: usize,
+        height: usize,
+    ) -> io::Result<()> {
+        queue!(out, PrintStyledContent(fit(title, width).bold()))?;
+        let lines = text.lines().skip(self.scroll);
+        for (row, line) in lines.take(height.saturating_sub(1)).enumerate() {
+            queue!(
+                out,
+                cursor::MoveTo(0, row as u16 + 1),
+                Print(fit(line, width))
+            )?;
+        }
+        Ok(())
+    }
+
+    fn run(&mut self, out: &mut Stdout, updates: &Receiver<Update>) -> Result<(), Box<dyn Error>> {
+        loop {
+            self.draw(out)?;
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && !self.handle_key(key.code) {
+                        return Ok(());
+                    }
+                }
+            }
+            while let Ok(update) = updates.try_recv() {
+                self.apply(update);
+            }
+        }
+    }
+}
+
+/// Shows every day in an interactive terminal UI where days can be rerun,
+/// their output inspected and their tests run.
+pub fn dashboard() -> Result<(), Box<dyn Error>> {
+    let (jobs, updates) = spawn_worker();
+    let mut dashboard = Dashboard {
+        days: days(Path::new(env!("CARGO_MANIFEST_DIR")))?
+            .into_iter()
+            .map(|name| Day {
+                name,
+                status: Status::NotRun,
+                output: String::new(),
+                tests: None,
+            })
+            .collect(),
+        selected: 0,
+        top: 0,
+        scroll: 0,
+        view: View::List,
+        jobs,
+    };
+    (0..dashboard.days.len()).for_each(|i| dashboard.queue_run(i));
+
+    let mut out = io::stdout();
+    let _guard = TerminalGuard::new(&mut out)?;
+    dashboard.run(&mut out, &updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_pads_and_truncates() {
+        assert_eq!(fit("ab", 4), "ab  ");
+        assert_eq!(fit("abcdef", 4), "abcd");
+        assert_eq!(fit("█μs", 2), "█μ");
+    }
+
+    #[test]
+    fn scrolls_just_enough_to_show_the_selection() {
+        assert_eq!(scroll_to_show(0, 3, 5), 0);
+        assert_eq!(scroll_to_show(0, 7, 5), 3);
+        assert_eq!(scroll_to_show(3, 5, 5), 3);
+        assert_eq!(scroll_to_show(3, 1, 5), 1);
+        assert_eq!(scroll_to_show(4, 4, 1), 4);
+    }
+
+    #[test]
+    fn queues_each_day_once_until_it_has_run() {
+        let (jobs, queued) = mpsc::channel();
+        let mut dashboard = Dashboard {
+            days: ["01", "02"]
+                .map(|name| Day {
+                    name: name.to_string(),
+                    status: Status::NotRun,
+                    output: String::new(),
+                    tests: None,
+                })
+                .into(),
+            selected: 0,
+            top: 0,
+            scroll: 0,
+            view: View::List,
+            jobs,
+        };
+        let sent = |queued: &Receiver<Job>| {
+            queued
+                .try_iter()
+                .map(|job| match job {
+                    Job::Run(i, _) | Job::Test(i, _) => i,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        dashboard.handle_key(KeyCode::Char('r'));
+        dashboard.handle_key(KeyCode::Char('a'));
+        assert_eq!(sent(&queued), [0, 1]);
+        dashboard.apply(Update::Started(0));
+        dashboard.handle_key(KeyCode::Char('r'));
+        assert_eq!(sent(&queued), []);
+        dashboard.apply(Update::Ran(0, Err("failed".to_string())));
+        dashboard.handle_key(KeyCode::Char('r'));
+        assert_eq!(sent(&queued), [0]);
+    }
+}
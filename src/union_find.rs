@@ -0,0 +1,177 @@
+use crate::{grid::Grid, point::Point};
+
+/// A disjoint-set forest over the elements `0..len`, with path compression
+/// and union by size.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    /// `len` elements, each in a set of its own.
+    pub fn new(len: usize) -> UnionFind {
+        UnionFind {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            components: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// The representative of the set containing `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut x = x;
+        while self.parents[x] != root {
+            x = std::mem::replace(&mut self.parents[x], root);
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`, returning false if they were
+    /// already the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (big, small) = if self.sizes[a] >= self.sizes[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parents[small] = big;
+        self.sizes[big] += self.sizes[small];
+        self.components -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The number of elements in the set containing `x`.
+    pub fn size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.sizes[root]
+    }
+
+    /// The number of disjoint sets.
+    pub fn components(&self) -> usize {
+        self.components
+    }
+}
+
+/// The connected regions of a grid, as found by [`label_components`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Components {
+    /// Each cell's component, numbered in reading order of their first cell,
+    /// or `None` for cells outside every component.
+    pub labels: Grid<Option<usize>>,
+    /// The number of cells in each component.
+    pub sizes: Vec<usize>,
+}
+
+/// Groups the cells satisfying `include` into components of orthogonally
+/// adjacent cells.
+pub fn label_components<T>(grid: &Grid<T>, include: impl Fn(&T) -> bool) -> Components {
+    let width = grid.width();
+    let index = |p: Point<usize>| p.y * width + p.x;
+    let mut sets = UnionFind::new(width * grid.height());
+    for (p, value) in grid.iter() {
+        if !include(value) {
+            continue;
+        }
+        for next in [Point::new(p.x + 1, p.y), Point::new(p.x, p.y + 1)] {
+            if grid.get(next).is_some_and(&include) {
+                sets.union(index(p), index(next));
+            }
+        }
+    }
+
+    let mut root_labels = vec![None; sets.len()];
+    let mut sizes = vec![];
+    let labels = grid
+        .iter()
+        .map(|(p, value)| {
+            if !include(value) {
+                return None;
+            }
+            let root = sets.find(index(p));
+            Some(*root_labels[root].get_or_insert_with(|| {
+                sizes.push(sets.sizes[root]);
+                sizes.len() - 1
+            }))
+        })
+        .collect();
+    Components {
+        labels: Grid::from_vec(width, grid.height(), labels).unwrap(),
+        sizes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unions_and_finds() {
+        let mut sets = UnionFind::new(6);
+        assert_eq!(sets.components(), 6);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+        assert!(sets.connected(0, 3));
+        assert!(!sets.connected(0, 4));
+        assert_eq!(sets.size(2), 4);
+        assert_eq!(sets.size(5), 1);
+        assert_eq!(sets.components(), 3);
+    }
+
+    #[test]
+    fn compresses_long_chains() {
+        let mut sets = UnionFind::new(1000);
+        for i in 1..1000 {
+            sets.union(i - 1, i);
+        }
+        assert_eq!(sets.size(0), 1000);
+        assert_eq!(sets.components(), 1);
+        let root = sets.find(999);
+        assert!((0..1000).all(|i| sets.parents[i] == root));
+    }
+
+    #[test]
+    fn labels_grid_components() {
+        let grid = Grid::parse_chars(
+            "##.#
+#..#
+..##
+#...",
+        )
+        .unwrap();
+        let components = label_components(&grid, |&c| c == '#');
+        assert_eq!(components.sizes, vec![3, 4, 1]);
+        assert_eq!(
+            components.labels.rows().collect::<Vec<_>>(),
+            vec![
+                [Some(0), Some(0), None, Some(1)],
+                [Some(0), None, None, Some(1)],
+                [None, None, Some(1), Some(1)],
+                [Some(2), None, None, None],
+            ]
+        );
+    }
+}